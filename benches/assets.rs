@@ -0,0 +1,58 @@
+use std::{hint::black_box, time::{Duration, Instant}};
+
+use rad::{Assets, Handle};
+
+const SIZES : [usize; 4] = [1_000, 10_000, 100_000, 500_000];
+const OPERATIONS : usize = 10_000;
+
+fn per_op(elapsed : Duration) -> f64
+{
+    elapsed.as_nanos() as f64 / OPERATIONS as f64
+}
+
+/// Fills an Assets with `size` assets then frees every other one so the free list is full of holes
+fn populated(size : usize) -> (Assets<u64>, Vec<Handle<u64>>)
+{
+    let mut assets = Assets::new();
+    let mut handles : Vec<_> = (0..size as u64).map(|v| assets.add_asset(v)).collect();
+
+    let mut i = 0;
+    handles.retain(|_| { i += 1; i % 2 == 0 });
+    assets.cleanup();
+
+    (assets, handles)
+}
+
+fn main()
+{
+    println!("{:>10} {:>14} {:>14} {:>14}", "assets", "insert ns/op", "remove ns/op", "get ns/op");
+
+    for size in SIZES
+    {
+        let (mut assets, alive) = populated(size);
+
+        let start = Instant::now();
+        let mut inserted = Vec::with_capacity(OPERATIONS);
+        for v in 0..OPERATIONS as u64
+        {
+            inserted.push(assets.add_asset(black_box(v)).downgrade());
+        }
+        let insert = per_op(start.elapsed());
+
+        let start = Instant::now();
+        for handle in &inserted
+        {
+            black_box(assets.remove_asset(handle).unwrap());
+        }
+        let remove = per_op(start.elapsed());
+
+        let start = Instant::now();
+        for handle in alive.iter().cycle().take(OPERATIONS)
+        {
+            black_box(assets.get_asset(handle));
+        }
+        let get = per_op(start.elapsed());
+
+        println!("{:>10} {:>14.1} {:>14.1} {:>14.1}", size, insert, remove, get);
+    }
+}
@@ -1,17 +1,5 @@
-use std::time::Instant;
 use rad::*;
 
-#[derive(Resource)]
-pub struct Time(pub Instant);
-
-impl Time
-{
-    pub fn elapsed(&self) -> f32
-    {
-        self.0.elapsed().as_secs_f32()
-    }
-}
-
 #[derive(Component)]
 pub struct Rotate(pub f32);
 
@@ -24,6 +12,7 @@ pub struct RainbowLight;
 fn main()
 {
     let mut app = App::new();
+    app.add_plugins(DefaultPlugins);
 
     let mesh = app.register_mesh(MeshBuilder::from_obj(include_str!("res/monkey.obj")));
     
@@ -44,9 +33,6 @@ fn main()
     cam_bundle.camera.draw_params.clear_colour = Some((0., 0., 0.2, 1.));
     app.spawn((MoveInCircle(Vector::new2(0., 0.), 2.5, 0.), cam_bundle));
 
-    // To Track Elapsed Time from Start
-    app.world.insert_resource(Time(Instant::now()));
-
     // Update Mesh and Camera Positions
     app.add_systems(ScheduleTimes::Update, (rotating, move_in_circle));
 
@@ -65,8 +51,8 @@ fn move_in_circle(mut query : Query<(&MoveInCircle, &mut Transform)>, time : Res
 {
     for (circle_params, mut transform) in query.iter_mut()
     {
-        transform.position.z = (time.elapsed() + circle_params.2).sin() * circle_params.1 + circle_params.0.z;
-        transform.position.x = (time.elapsed() + circle_params.2).cos() * circle_params.1 + circle_params.0.x;
+        transform.position.z = (time.elapsed_seconds() + circle_params.2).sin() * circle_params.1 + circle_params.0.z;
+        transform.position.x = (time.elapsed_seconds() + circle_params.2).cos() * circle_params.1 + circle_params.0.x;
         transform.position.y = circle_params.0.y;
     }
 }
\ No newline at end of file
@@ -0,0 +1,37 @@
+use rad::*;
+
+// Packs a folder of assets into one archive, e.g.
+// cargo run --example pack -- examples/res assets.radpack
+// then load from it with AssetPlugin { archive: Some("assets.radpack".into()), ..Default::default() }
+
+fn main()
+{
+    let args : Vec<String> = std::env::args().skip(1).collect();
+
+    let (dir, out, compress) = match args.as_slice()
+    {
+        [dir, out] => (dir, out, true),
+        [dir, out, flag] if flag == "--no-compress" => (dir, out, false),
+        _ => {
+            println!("Usage: pack <asset folder> <archive> [--no-compress]");
+            return;
+        }
+    };
+
+    ArchiveBuilder::new()
+        .compress(compress)
+        .add_dir(dir)
+        .and_then(|builder| builder.write(out))
+        .expect("Failed to pack assets");
+
+    let archive = Archive::open(out).expect("Failed to read back the archive");
+    let mut entries : Vec<_> = archive.entries().collect();
+    entries.sort_by_key(|(path, _)| *path);
+
+    for (path, entry) in entries
+    {
+        // Reading checks the content hash
+        archive.read(path).expect("Packed file doesn't match");
+        println!("{:<40} {:>10} bytes {:016x}{}", path, entry.len, entry.hash, if entry.compressed { " (compressed)" } else { "" });
+    }
+}
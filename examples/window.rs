@@ -2,7 +2,8 @@ use rad::*;
 
 fn main()
 {
-    let app : App = App::new();
+    let mut app : App = App::new();
+    app.add_plugins(DefaultPlugins);
  
     // App has taken complete control over the main thread, we are not getting control back
     app.run();
@@ -1,7 +1,7 @@
-use std::sync::Mutex;
+use std::{rc::Rc, sync::Mutex};
 
 use crate::{
-    AssetHandle, Assets, BakedCameraInformation, Colour, OpenGL, RenderAPI, Transform, Vertex,
+    Assets, Handle, Shader, BakedCameraInformation, Colour, OpenGL, RadError, RenderAPI, Transform,
 };
 use bevy_ecs::{
     component::Component,
@@ -10,12 +10,13 @@ use bevy_ecs::{
     world::World,
 };
 use glium::{
-    backend::Backend,
-    glutin::surface::WindowSurface,
+    backend::{Backend, Context},
     program::{self, ShaderStage},
     uniform,
     uniforms::{UniformBuffer, Uniforms, UniformsStorage},
-    Display, Frame, IndexBuffer, Program, Surface, VertexBuffer,
+    index::IndexBufferAny,
+    vertex::MultiVerticesSource,
+    Frame, Program, Surface,
 };
 
 
@@ -28,6 +29,10 @@ pub struct DefaultMaterial {
     pub shading_enabled: bool,
 
     pub base_colour: Colour,
+
+    /// Drawn with this program instead of the built-in one, e.g. a shader loaded through the AssetServer
+    /// so it can be hot reloaded. The built-in one is used until it has loaded.
+    pub shader: Option<Handle<Program>>,
 }
 
 impl Default for DefaultMaterial {
@@ -35,11 +40,12 @@ impl Default for DefaultMaterial {
         Self {
             shading_enabled: true,
             base_colour: Colour::WHITE,
+            shader: None,
         }
     }
 }
 
-static MATERIAL_SHADER_HANDLE: Mutex<Option<AssetHandle>> = Mutex::new(None);
+static MATERIAL_SHADER_HANDLE: Mutex<Option<Handle<Program>>> = Mutex::new(None);
 impl DefaultMaterial {
     const VS_SOURCE: &'static str = r#"
     #version 400
@@ -83,15 +89,15 @@ impl DefaultMaterial {
     }
 "#;
 
-    pub(crate) fn glium_register(display: &Display<WindowSurface>, shaders: &mut Assets<Program>) {
+    pub(crate) fn glium_register(display: &Rc<Context>, shaders: &mut Assets<Program>) -> Result<(), RadError> {
         if MATERIAL_SHADER_HANDLE.lock().unwrap().is_some() {
-            return;
+            return Ok(());
         }
 
-        let program =
-            Program::from_source(display, Self::VS_SOURCE, Self::FS_SOURCE, None).unwrap();
+        let program = Program::from_source(display, Self::VS_SOURCE, Self::FS_SOURCE, None)?;
 
         *MATERIAL_SHADER_HANDLE.lock().unwrap() = Some(shaders.add_asset(program));
+        Ok(())
     }
 
     pub fn new(base_colour: Colour) -> DefaultMaterial {
@@ -101,20 +107,30 @@ impl DefaultMaterial {
         }
     }
 
-    pub fn draw_glium(
+    /// Draws with `shader` instead of the built-in program, see [`DefaultMaterial::shader`]
+    pub fn with_shader(mut self, shader: &Shader) -> DefaultMaterial {
+        self.shader = Some(shader.handle.clone());
+        self
+    }
+
+    /// `vertices` is a `&VertexBuffer<Vertex>`, or a tuple of it and the mesh's other vertex buffers
+    pub fn draw_glium<'a, V: MultiVerticesSource<'a>>(
         &self,
         context: &mut Frame,
         transform: Option<&Transform>,
         baked_camera: &BakedCameraInformation,
-        buffers: (&VertexBuffer<Vertex>, &IndexBuffer<u16>),
+        (vertices, indices): (V, &IndexBufferAny),
         programs: &Assets<Program>,
-    ) -> Result<(), glium::DrawError> {
+    ) -> Result<(), RadError> {
+        let program = self.shader.as_ref()
+            .and_then(|handle| programs.get_asset(handle))
+            .or_else(|| programs.get_asset(MATERIAL_SHADER_HANDLE.lock().unwrap().as_ref()?))
+            .ok_or(RadError::InvalidHandle)?;
+
         context.draw(
-            buffers.0,
-            buffers.1,
-            programs
-                .get_asset(&MATERIAL_SHADER_HANDLE.lock().unwrap().unwrap())
-                .unwrap(),
+            vertices,
+            indices,
+            program,
             &uniform! {
                 model : transform.unwrap_or(&Transform::new()).as_uniform(),
                 projection : baked_camera.projection,
@@ -126,7 +142,8 @@ impl DefaultMaterial {
                 shade: (if self.shading_enabled {"shading_enabled"} else {"shading_disabled"}, ShaderStage::Fragment)
             },
             &OpenGL::default_draw_params(),
-        )
+        )?;
+        Ok(())
     }
 
     // pub fn default_component() -> Material<DefaultMaterial> {
@@ -1,6 +1,8 @@
-use std::marker::PhantomData;
+use std::{error::Error, marker::PhantomData};
 
-use crate::AssetHandle;
+use glium::Program;
+
+use crate::{read_u32, AssetLoader, Handle, LoadContext, RadError, GLSL};
 
 pub struct ShaderSource<T>
 {
@@ -9,7 +11,106 @@ pub struct ShaderSource<T>
     _ty : PhantomData<T> // Compile Time Enforcement for correct shading languages
 }
 
+impl<T> ShaderSource<T>
+{
+    pub fn new(vertex_source : impl Into<String>, fragment_source : impl Into<String>) -> Self
+    {
+        Self { vertex_source: vertex_source.into(), fragment_source: fragment_source.into(), _ty: PhantomData }
+    }
+}
+
+impl ShaderSource<GLSL>
+{
+    /// Splits a single file into its stages, each starting with a `#shader vertex` or `#shader fragment` line
+    pub fn from_combined(source : &str) -> Result<Self, RadError>
+    {
+        let mut vertex_source = None;
+        let mut fragment_source = None;
+        let mut current : Option<&mut Option<String>> = None;
+
+        for line in source.lines()
+        {
+            if let Some(stage) = line.trim().strip_prefix("#shader")
+            {
+                current = match stage.trim()
+                {
+                    "vertex" => Some(&mut vertex_source),
+                    "fragment" => Some(&mut fragment_source),
+                    other => return Err(RadError::Parse(format!("Unknown shader stage \"{}\"", other))),
+                };
+                current.as_mut().unwrap().get_or_insert_with(String::new);
+                continue;
+            }
+
+            if let Some(Some(stage_source)) = current.as_mut()
+            {
+                stage_source.push_str(line);
+                stage_source.push('\n');
+            }
+        }
+
+        match (vertex_source, fragment_source)
+        {
+            (Some(vertex_source), Some(fragment_source)) => Ok(Self::new(vertex_source, fragment_source)),
+            (None, _) => Err(RadError::Parse(String::from("Missing a #shader vertex section"))),
+            (_, None) => Err(RadError::Parse(String::from("Missing a #shader fragment section"))),
+        }
+    }
+}
+
 pub struct Shader
 {
-    pub handle : AssetHandle
-}
\ No newline at end of file
+    pub handle : Handle<Program>
+}
+
+/// Loads GLSL shaders from a single file, see [`ShaderSource::from_combined`]
+pub struct GlslLoader;
+
+impl AssetLoader for GlslLoader
+{
+    type Asset = Program;
+
+    fn extensions(&self) -> &[&str]
+    {
+        &["glsl"]
+    }
+
+    fn load(&self, bytes : Vec<u8>, _context : &mut LoadContext) -> Result<ShaderSource<GLSL>, Box<dyn Error + Send + Sync>>
+    {
+        Ok(ShaderSource::from_combined(&String::from_utf8(bytes)?)?)
+    }
+
+    fn processed_format(&self) -> Option<&str>
+    {
+        Some("rad.glsl.1")
+    }
+
+    /// Each stage's length followed by its source, so loading skips splitting the file
+    fn process(&self, loaded : &ShaderSource<GLSL>) -> Option<Vec<u8>>
+    {
+        let mut bytes = vec![];
+        for source in [&loaded.vertex_source, &loaded.fragment_source]
+        {
+            bytes.extend_from_slice(&(source.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(source.as_bytes());
+        }
+        Some(bytes)
+    }
+
+    fn load_processed(&self, bytes : Vec<u8>) -> Result<ShaderSource<GLSL>, Box<dyn Error + Send + Sync>>
+    {
+        let mut reader = bytes.as_slice();
+        let mut stages = [String::new(), String::new()];
+
+        for stage in &mut stages
+        {
+            let len = read_u32(&mut reader)? as usize;
+            let source = reader.get(..len).ok_or_else(|| RadError::Parse(String::from("Shader data ends before its last stage")))?;
+            *stage = String::from_utf8(source.to_vec())?;
+            reader = &reader[len..];
+        }
+
+        let [vertex_source, fragment_source] = stages;
+        Ok(ShaderSource::new(vertex_source, fragment_source))
+    }
+}
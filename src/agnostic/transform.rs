@@ -26,6 +26,37 @@ impl Transform {
         }
     }
 
+    /// The closest Transform to a column major matrix like [`Transform::as_uniform`] returns.
+    /// Transforms only scale uniformly, so the scale is the average along each axis and mirroring is lost.
+    pub fn from_matrix(matrix : [[f32; 4]; 4]) -> Self
+    {
+        let axes = [0, 1, 2].map(|column| [matrix[column][0], matrix[column][1], matrix[column][2]]);
+        let lengths = axes.map(|axis| (axis[0] * axis[0] + axis[1] * axis[1] + axis[2] * axis[2]).sqrt());
+
+        // The rotation matrix at row, column, a collapsed axis is treated as unrotated
+        let rotation = |row : usize, column : usize| match lengths[column]
+        {
+            length if length > 0. => axes[column][row] / length,
+            _ => if row == column { 1. } else { 0. },
+        };
+
+        // into_matrix rotates around x, then y, then z, so the rotation matrix is Rx * Ry * Rz
+        let y = rotation(0, 2).clamp(-1., 1.).asin();
+        let (x, z) = if rotation(0, 2).abs() < 0.9999
+        {
+            ((-rotation(1, 2)).atan2(rotation(2, 2)), (-rotation(0, 1)).atan2(rotation(0, 0)))
+        } else {
+            // Looking straight along y, x and z turn around the same axis so z is left at 0
+            (rotation(2, 1).atan2(rotation(1, 1)), 0.)
+        };
+
+        Self {
+            position: Vector::new3(matrix[3][0], matrix[3][1], matrix[3][2]),
+            rotation: Vector::new3(x, y, z),
+            scale: (lengths[0] + lengths[1] + lengths[2]) / 3.,
+        }
+    }
+
     pub fn into_matrix(&self) -> Mat4
     {
         let mut matrix = Mat4::identity();
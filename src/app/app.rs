@@ -1,22 +1,15 @@
-use std::{any::TypeId, collections::HashMap, default, f32::consts::E, process::exit, time::Instant};
+use std::{any::TypeId, default, f32::consts::E, sync::Arc, thread::sleep, time::{Duration, Instant}};
 
 use bevy_ecs::{
     bundle::Bundle,
-    schedule::{ExecutorKind, IntoSystemConfigs, Schedule, ScheduleLabel},
+    event::{event_update_condition, event_update_system, reset_event_update_signal_system, signal_event_update_system, Event, EventUpdateSignal, EventUpdates, Events},
+    schedule::{run_enter_schedule, ExecutorKind, IntoSystemConfigs, Schedule, ScheduleLabel},
     world::{EntityWorldMut, World},
 };
-use winit::{dpi::LogicalSize, event::WindowEvent, event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget}, window::{Window, WindowBuilder}};
+use winit::{dpi::LogicalSize, event::{ElementState, WindowEvent}, event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget}, keyboard::PhysicalKey, window::{Window, WindowBuilder}};
 
 use crate::*;
 
-// TODO: V-Sync and other options
-pub const FRAME_RATE : f32 = 60.;
-
-#[derive(Resource)]
-pub struct DeltaTime {
-    pub delta_time: f64,
-}
-
 #[derive(ScheduleLabel, Default, Hash, Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub enum ScheduleTimes {
     #[default]
@@ -28,6 +21,14 @@ pub enum ScheduleTimes {
 
 pub use ScheduleTimes::*;
 
+/// Runs before `Update`, applying queued [`NextState`] transitions for every state added with [`App::init_state`]
+#[derive(ScheduleLabel, Hash, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct StateTransition;
+
+/// Runs once per frame before `Draw`, swapping the buffers of every event added with [`App::add_event`]
+#[derive(ScheduleLabel, Hash, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct EventUpdate;
+
 #[derive(Resource, Clone)]
 pub struct WindowResource
 {
@@ -35,45 +36,115 @@ pub struct WindowResource
     title : String
 }
 
+impl WindowResource
+{
+    pub fn size(&self) -> (u32, u32)
+    {
+        self.size
+    }
+}
+
 pub struct App {
-    api: Box<OpenGL>,
     pub world: World,
-    pub(crate) schedules: HashMap<ScheduleTimes, Schedule>,
+    schedule_order : ScheduleOrder,
+    plugins : Vec<(TypeId, String)>,
+    started_running : bool,
     finished_running : bool,
 
-    window : Window,
+    window : Option<Window>,
     event_loop : Option<EventLoop<()>>,
 
-    last_frame : Instant
+    last_frame : Instant,
+    accumulator : Duration,
+    redraw_pending : bool,
+    /// The V-Sync setting the RenderAPI was last given, None before the first frame
+    vsync : Option<VSync>,
 }
 
 impl App {
+    /// Creates an App with an OpenGL window.
+    /// No plugins are added, add [`DefaultPlugins`] for time, input and rendering.
+    /// Panics if the window can't be created, see [`App::try_with_window_builder`]
     pub fn with_window_builder(builder : WindowBuilder) -> Self
     {
-        let mut schedules = HashMap::new();
-        // TODO: Make a system that is more viable than this
-        for time in vec![Startup, Update, Draw, End] {
-            schedules.insert(time, Schedule::new(time));
-        }
+        Self::try_with_window_builder(builder).unwrap_or_else(|err| panic!("{}", err))
+    }
 
-        schedules.get_mut(&Draw).unwrap().set_executor_kind(ExecutorKind::Simple);
+    pub fn try_with_window_builder(builder : WindowBuilder) -> Result<Self, RadError>
+    {
+        Self::try_with_frame_pacing(builder, FramePacing::default())
+    }
 
-        let (window, event_loop, api) = OpenGL::init(builder);
+    /// Same as [`App::with_window_builder`], starting with `pacing` instead of the default [`FramePacing`].
+    /// Panics if the window can't be created, see [`App::try_with_frame_pacing`]
+    pub fn with_frame_pacing(builder : WindowBuilder, pacing : FramePacing) -> Self
+    {
+        Self::try_with_frame_pacing(builder, pacing).unwrap_or_else(|err| panic!("{}", err))
+    }
 
+    pub fn try_with_frame_pacing(builder : WindowBuilder, pacing : FramePacing) -> Result<Self, RadError>
+    {
+        let (window, event_loop, api) = OpenGL::init(builder)?;
+
+        let size = window.inner_size();
+        let mut app = Self::from_api(Box::new(api), Some(window), Some(event_loop));
+        app.world.resource_mut::<WindowResource>().size = (size.width, size.height);
+        app.world.insert_resource(pacing);
+        Ok(app)
+    }
+
+    /// Creates an App without a window or GL context.
+    /// Meshes and shaders are stored CPU side and nothing is drawn,
+    /// use [`App::step`] to advance it deterministically.
+    pub fn headless() -> Self
+    {
+        Self::from_api(Box::new(Headless::new()), None, None)
+    }
+
+    fn from_api(api : Box<dyn RenderAPI>, window : Option<Window>, event_loop : Option<EventLoop<()>>) -> Self
+    {
         let mut world = World::new();
+        world.init_resource::<Schedules>();
         world.insert_resource(WindowResource {size : (0,0 ), title : "".to_string()});
+        world.init_resource::<AppExit>();
+        world.init_resource::<FramePacing>();
+        world.init_resource::<EventUpdateSignal>();
+        world.insert_non_send_resource(RenderContext { api });
 
-        App {
-            api : Box::new(api),
+        let mut app = App {
             window,
-            event_loop : Some(event_loop),
+            event_loop,
             world,
-            schedules,
+            schedule_order : ScheduleOrder::new(),
+            plugins : vec![],
+            started_running : false,
             finished_running : false,
-            last_frame : Instant::now()
-        }
+            last_frame : Instant::now(),
+            accumulator : Duration::ZERO,
+            redraw_pending : false,
+            vsync : None,
+        };
+
+        app.add_schedule(Phase::Startup, Startup);
+        app.add_schedule(Phase::Fixed, Update);
+        app.add_schedule(Phase::Frame, Draw);
+        app.add_schedule(Phase::End, End);
+        app.add_schedule_before(EventUpdate, Draw);
+
+        app.set_executor_kind(Draw, ExecutorKind::Simple);
+
+        // Events are only swapped on frames where Update ran, so Update never misses any
+        app.add_systems(Update, signal_event_update_system);
+        app.add_systems(EventUpdate, reset_event_update_signal_system.after(EventUpdates));
+
+        app.add_event::<WindowEvents>();
+        app.add_event::<DrawFailed>();
+        app.add_event::<VSyncFailed>();
+
+        app
     }
 
+    /// A 480x480 window, see [`App::with_window_builder`]. Rendering, time and input come from [`DefaultPlugins`].
     pub fn new() -> Self {
         Self::with_window_builder(
             WindowBuilder::new()
@@ -87,108 +158,560 @@ impl App {
         self.last_frame.elapsed().as_secs_f32()
     }
 
-    pub fn run(mut self) -> ! {
-        let event_loop = self.event_loop.take().unwrap();
+    /// Takes control of the main thread until the window is closed or [`AppExit`] is requested.
+    /// Panics if the event loop fails, see [`App::try_run`]
+    pub fn run(self) {
+        self.try_run().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Same as [`App::run`], giving back the error if the event loop fails
+    pub fn try_run(mut self) -> Result<(), RadError> {
+        self.run_startup();
+        self.last_frame = Instant::now();
+
+        let Some(event_loop) = self.event_loop.take() else {
+            // Headless, there is nothing to wait on so just tick in real time
+            while !self.finished_running {
+                self.frame();
+                sleep(self.fixed_step().0);
+            }
+            return Ok(());
+        };
+
         event_loop.run(move |event, target| {
+            if self.finished_running
+            {
+                target.exit();
+                return;
+            }
+
             match event
             {
                 winit::event::Event::WindowEvent { window_id, event } => self.window_event(event, target),
-                winit::event::Event::AboutToWait => {
-                    if self.delta_time() > 1. / FRAME_RATE
-                    {
-                        self.window.request_redraw();
-                    }
-
-                    target.set_control_flow(ControlFlow::Poll)
-                },
+                winit::event::Event::AboutToWait => self.pace_frame(target),
                 _ => {}
             }
-        }).unwrap();
-        exit(0);
+        }).map_err(|err| RadError::Window(format!("Event loop failed - {}", err)))
+    }
+
+    /// Decides whether to redraw now and how long the event loop may sleep for
+    fn pace_frame(&mut self, target : &EventLoopWindowTarget<()>)
+    {
+        let pacing = *self.world.resource::<FramePacing>();
+        let since_last_frame = self.last_frame.elapsed();
+
+        let (redraw, control_flow) = match pacing.limit
+        {
+            FrameLimit::Unlimited => (true, ControlFlow::Poll),
+            FrameLimit::Capped(frame_rate) =>
+            {
+                let interval = Duration::from_secs_f32(1. / frame_rate.max(f32::EPSILON));
+                if since_last_frame >= interval
+                {
+                    (true, ControlFlow::WaitUntil(Instant::now() + interval))
+                } else {
+                    (false, ControlFlow::WaitUntil(self.last_frame + interval))
+                }
+            },
+            FrameLimit::LowPower { max_wait } =>
+            {
+                let timed_out = max_wait.is_some_and(|max_wait| since_last_frame >= max_wait);
+                let redraw = self.redraw_pending || timed_out;
+                match max_wait
+                {
+                    Some(max_wait) if redraw => (true, ControlFlow::WaitUntil(Instant::now() + max_wait)),
+                    Some(max_wait) => (false, ControlFlow::WaitUntil(self.last_frame + max_wait)),
+                    None => (redraw, ControlFlow::Wait),
+                }
+            },
+        };
+
+        if let (true, Some(window)) = (redraw, &self.window)
+        {
+            window.request_redraw();
+        }
+
+        target.set_control_flow(control_flow);
     }
 
     fn window_event(&mut self, window_event : WindowEvent, target : &EventLoopWindowTarget<()>)
     {
+        if !matches!(window_event, WindowEvent::RedrawRequested)
+        {
+            self.redraw_pending = true;
+        }
+
+        if let Some(mut input) = self.world.get_resource_mut::<Input>()
+        {
+            input.handle_window_event(&window_event);
+        }
+
         match window_event
         {
             WindowEvent::Resized(size) => 
             {
                 self.world.get_resource_mut::<WindowResource>().unwrap().size = (size.width, size.height);
+                self.send_event(WindowEvents::Resized(size.width, size.height));
+            },
+            WindowEvent::Focused(focused) =>
+            {
+                self.send_event(WindowEvents::Focused(focused));
+            },
+            WindowEvent::KeyboardInput { event, .. } =>
+            {
+                if let PhysicalKey::Code(code) = event.physical_key
+                {
+                    let action = match event.state
+                    {
+                        ElementState::Pressed if event.repeat => InputAction::Repeat,
+                        ElementState::Pressed => InputAction::Press,
+                        ElementState::Released => InputAction::Release,
+                    };
+                    self.send_event(KeyboardInput { key: Key::from_key_code(code), action });
+                }
+            },
+            WindowEvent::MouseInput { state, button, .. } =>
+            {
+                if let Some(button) = MouseButton::from_winit(button)
+                {
+                    let action = match state
+                    {
+                        ElementState::Pressed => InputAction::Press,
+                        ElementState::Released => InputAction::Release,
+                    };
+                    let position = self.world.get_resource::<Input>().map(|input| input.mouse_position()).unwrap_or_default();
+                    self.send_event(MouseButtonInput { button, action, position });
+                }
+            },
+            WindowEvent::CursorMoved { position, .. } =>
+            {
+                self.send_event(CursorMoved { position: (position.x as f32, position.y as f32) });
             },
             WindowEvent::CloseRequested => 
             {
-                target.exit();
+                self.send_event(WindowEvents::CloseRequested);
+                self.world.resource_mut::<AppExit>().request_with(ExitReason::WindowClosed);
+                // Systems get a frame to veto the request
+                if let Some(window) = &self.window
+                {
+                    window.request_redraw();
+                }
             },
             WindowEvent::RedrawRequested => 
             {
-                let mut cameras: QueryState<(&Camera, Option<&Transform>)> = self.world.query();
-                let mut baked_camera_information: Vec<BakedCameraInformation> = Vec::new();
-
-                let window_size = self.window.inner_size();
-                let window_size = (window_size.width, window_size.height);
-                for (camera, eye) in cameras.iter(&self.world) {
-                    baked_camera_information.push(camera.bake(eye, window_size, &vec![]));
-                }
+                self.frame();
 
-                for cam in baked_camera_information
+                if self.finished_running
                 {
-                    self.api.draw(&mut self.world, &cam);
+                    target.exit();
                 }
-
-                self.schedules.get_mut(&Draw).unwrap().run(&mut self.world);
-                self.last_frame = Instant::now();
             },
             _ => {}
         }
     }
 
+    /// Runs Startup if it hasn't been run yet, then advances `frames` frames
+    /// of exactly one fixed timestep each, regardless of real time passing
+    pub fn step(&mut self, frames : u32)
+    {
+        self.run_startup();
+
+        for _ in 0..frames
+        {
+            if self.finished_running
+            {
+                break;
+            }
+
+            let step = self.fixed_step().0;
+            self.frame_with_time(step);
+        }
+    }
+
+    fn frame(&mut self)
+    {
+        let frame_time = self.last_frame.elapsed();
+        self.last_frame = Instant::now();
+        self.redraw_pending = false;
+
+        self.frame_with_time(frame_time);
+    }
+
+    fn frame_with_time(&mut self, frame_time : Duration)
+    {
+        self.apply_vsync();
+
+        if let Some(mut time) = self.world.get_resource_mut::<Time>()
+        {
+            time.advance(frame_time);
+        }
+
+        self.step_fixed(frame_time);
+
+        let delta = self.world.get_resource::<Time>().map(|time| time.delta()).unwrap_or(frame_time);
+        self.set_delta_time(delta);
+
+        self.step_draw();
+
+        if let Some(mut context) = self.world.remove_non_send_resource::<RenderContext>()
+        {
+            context.api.cleanup(&mut self.world);
+            self.world.insert_non_send_resource(context);
+        }
+
+        if self.world.resource_mut::<AppExit>().resolve()
+        {
+            self.shutdown();
+        }
+    }
+
+    /// Hands [`FramePacing::vsync`] to the RenderAPI if it changed since the last frame
+    fn apply_vsync(&mut self)
+    {
+        let vsync = self.world.resource::<FramePacing>().vsync;
+        if self.vsync == Some(vsync)
+        {
+            return;
+        }
+
+        // Only tried once per change, a driver that refuses it will keep refusing
+        self.vsync = Some(vsync);
+        let Some(result) = self.world.get_non_send_resource_mut::<RenderContext>().map(|mut context| context.api.set_vsync(vsync)) else {
+            return;
+        };
+
+        if let (Err(err), Some(mut events)) = (result, self.world.get_resource_mut::<Events<VSyncFailed>>())
+        {
+            events.send(VSyncFailed { vsync, error: Arc::new(err) });
+        }
+    }
+
+    /// Keeps the deprecated [`DeltaTime`] in step with the clock the next schedule runs on
+    #[allow(deprecated)]
+    fn set_delta_time(&mut self, delta : Duration)
+    {
+        if let Some(mut delta_time) = self.world.get_resource_mut::<DeltaTime>()
+        {
+            delta_time.delta_time = delta.as_secs_f64();
+        }
+    }
+
+    /// Runs `Update` as many times as the accumulated frame time allows
+    fn step_fixed(&mut self, frame_time : Duration)
+    {
+        let (step, max_steps_per_frame) = self.fixed_step();
+        self.accumulator += frame_time;
+
+        let mut steps = 0;
+        while self.accumulator >= step && steps < max_steps_per_frame
+        {
+            let delta = self.world.get_resource::<Time>().map(|time| time.scale(step)).unwrap_or(step);
+            if let Some(mut fixed_time) = self.world.get_resource_mut::<FixedTime>()
+            {
+                fixed_time.tick(delta);
+            }
+            self.set_delta_time(delta);
+
+            self.step_update();
+            self.accumulator -= step;
+            steps += 1;
+        }
+
+        // Too far behind to catch up, drop the backlog instead of spiralling
+        if steps == max_steps_per_frame
+        {
+            self.accumulator = self.accumulator.min(step);
+        }
+
+        let overstep = self.accumulator.as_secs_f64() / step.as_secs_f64();
+        if let Some(mut fixed_time) = self.world.get_resource_mut::<FixedTime>()
+        {
+            fixed_time.set_overstep(overstep);
+        }
+    }
+
+    /// Runs `End` then drops the RenderAPI and window, freeing GPU resources
+    fn shutdown(&mut self)
+    {
+        self.end();
+
+        drop(self.world.remove_non_send_resource::<RenderContext>());
+        self.window = None;
+    }
+
+    /// Fixed step length and catch up limit, from [`FixedTime`] if it exists
+    fn fixed_step(&self) -> (Duration, u32)
+    {
+        let fixed_time = self.world.get_resource::<FixedTime>().cloned().unwrap_or_default();
+        (fixed_time.step(), fixed_time.max_steps_per_frame)
+    }
+
+    /// Panics if the mesh can't be created, see [`App::try_register_mesh`]
     pub fn register_mesh(&mut self, builder : MeshBuilder) -> Mesh
     {
-        self.api.create_mesh(builder)
+        self.world.non_send_resource_mut::<RenderContext>().api.create_mesh(builder)
+    }
+
+    pub fn try_register_mesh(&mut self, builder : MeshBuilder) -> Result<Mesh, RadError>
+    {
+        self.world.non_send_resource_mut::<RenderContext>().api.try_create_mesh(builder)
+    }
+
+    /// Hands every primitive of a parsed glTF file to the RenderAPI, panics if one can't be created, see [`App::try_register_gltf`]
+    pub fn register_gltf(&mut self, gltf : Gltf<MeshBuilder>) -> Gltf
+    {
+        self.try_register_gltf(gltf).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_register_gltf(&mut self, gltf : Gltf<MeshBuilder>) -> Result<Gltf, RadError>
+    {
+        let api = &mut self.world.non_send_resource_mut::<RenderContext>().api;
+        gltf.map_meshes(|_, _, builder| api.try_create_mesh(builder))
+    }
+
+    /// Panics with the compile or link log if the program can't be created, see [`App::try_create_program`]
+    pub fn create_program(&mut self, shader : &ShaderSource<GLSL>) -> Shader
+    {
+        self.world.non_send_resource_mut::<RenderContext>().api.create_program(shader)
+    }
+
+    pub fn try_create_program(&mut self, shader : &ShaderSource<GLSL>) -> Result<Shader, RadError>
+    {
+        self.world.non_send_resource_mut::<RenderContext>().api.try_create_program(shader)
     }
 
     pub fn run_startup(&mut self) {
-        if let Some(mut startup) = self.schedules.remove(&Startup)
+        if self.started_running
         {
-            startup.run(&mut self.world);
+            return;
         }
+
+        self.started_running = true;
+        self.run_phase(Phase::Startup);
     }
 
     pub fn end(&mut self) {
-        if let Some(mut end) = self.schedules.remove(&End)
+        if self.finished_running
         {
-            end.run(&mut self.world);
-            self.finished_running = true;
+            return;
         }
+
+        self.run_phase(Phase::End);
+        self.finished_running = true;
     }
 
     pub fn step_draw(&mut self) {
-        self.schedules
-            .get_mut(&Draw)
-            .unwrap()
-            .run(&mut self.world);
+        self.run_phase(Phase::Frame);
     }
 
     pub fn step_update(&mut self) {
-        self.schedules
-            .get_mut(&Update)
-            .unwrap()
-            .run(&mut self.world);
+        self.run_phase(Phase::Fixed);
+    }
+
+    fn run_phase(&mut self, phase : Phase)
+    {
+        for label in self.schedule_order.labels(phase)
+        {
+            self.world.run_schedule(label);
+        }
+    }
+
+    /// Adds a [`Plugin`], [`PluginGroup`] or a tuple of them to the App
+    pub fn add_plugins<M>(&mut self, plugins : impl Plugins<M>) -> &mut Self
+    {
+        plugins.add_to_app(self);
+        self
+    }
+
+    pub fn add_plugin<T : Plugin>(&mut self, plugin : T) -> &mut Self
+    {
+        self.add_boxed_plugin(TypeId::of::<T>(), Box::new(plugin))
+    }
+
+    pub(crate) fn add_boxed_plugin(&mut self, type_id : TypeId, plugin : Box<dyn Plugin>) -> &mut Self
+    {
+        if plugin.is_unique() && self.plugins.iter().any(|(_, name)| name == plugin.name())
+        {
+            panic!("Plugin {} has already been added to the App", plugin.name());
+        }
+
+        self.plugins.push((type_id, plugin.name().to_string()));
+        plugin.build(self);
+        self
+    }
+
+    pub fn is_plugin_added<T : Plugin>(&self) -> bool
+    {
+        self.plugins.iter().any(|(id, _)| *id == TypeId::of::<T>())
+    }
+
+    /// Registers an event type, its buffers are swapped once per frame.
+    /// Readers in `Update` see every event, readers in `Draw` see events from this and the previous frame.
+    pub fn add_event<T : Event>(&mut self) -> &mut Self
+    {
+        if !self.world.contains_resource::<Events<T>>()
+        {
+            self.world.init_resource::<Events<T>>();
+            self.add_systems(EventUpdate, event_update_system::<T>
+                .in_set(EventUpdates)
+                .run_if(event_update_condition::<T>));
+        }
+        self
+    }
+
+    /// Lets the [`AssetServer`] load files with the loader's extensions
+    pub fn add_asset_loader<L : AssetLoader>(&mut self, loader : L) -> &mut Self
+    {
+        self.world.resource_mut::<AssetServer>().add_loader(loader);
+        self
+    }
+
+    /// Stores `T` in an [`Assets<T>`] resource, for assets that don't live in the RenderAPI.
+    /// The AssetServer can then load it, unreferenced assets are freed each frame and [`AssetEvent<T>`]s are sent.
+    pub fn init_asset<T : Asset + Send + Sync>(&mut self) -> &mut Self
+    {
+        if self.world.contains_resource::<Assets<T>>()
+        {
+            return self
+        }
+
+        let mut assets = Assets::<T>::new();
+        assets.enable_events();
+        self.world.resource_mut::<AssetServer>().register(assets.reserver());
+        self.world.insert_resource(assets);
+
+        self.add_event::<AssetEvent<T>>()
+            .add_systems(LoadAssets, |mut assets : ResMut<Assets<T>>, mut events : EventWriter<AssetEvent<T>>|
+            {
+                assets.cleanup();
+                events.send_batch(assets.drain_events());
+            })
+    }
+
+    /// Adds a [`State`] starting at `S::default()`, see [`App::insert_state`]
+    pub fn init_state<S : States + Default>(&mut self) -> &mut Self
+    {
+        self.insert_state(S::default())
+    }
+
+    /// Adds a [`State`] starting at `state`.
+    /// Transitions queued with [`NextState::set`] are applied before the next `Update`,
+    /// running the `OnExit(old)`, `OnTransition { from, to }` and `OnEnter(new)` schedules.
+    /// Use `in_state(S::Variant)` as a run condition for systems that only run in one state.
+    pub fn insert_state<S : States>(&mut self, state : S) -> &mut Self
+    {
+        if self.world.contains_resource::<State<S>>()
+        {
+            return self;
+        }
+
+        self.world.insert_resource(State::new(state));
+        self.world.init_resource::<NextState<S>>();
+        self.add_event::<StateTransitionEvent<S>>();
+
+        if !self.schedule_order.contains(StateTransition)
+        {
+            self.add_schedule_before(StateTransition, Update);
+        }
+
+        self.add_systems(StateTransition, (
+            run_enter_schedule::<S>.run_if(run_once()),
+            apply_state_transition::<S>,
+        ).chain());
+
+        self
+    }
+
+    /// Sends an event if its type has been registered with [`App::add_event`]
+    pub fn send_event<T : Event>(&mut self, event : T)
+    {
+        if let Some(mut events) = self.world.get_resource_mut::<Events<T>>()
+        {
+            events.send(event);
+        }
     }
 
     pub fn spawn<B: Bundle>(&mut self, bundle: B) -> EntityWorldMut {
         self.world.spawn(bundle)
     }
 
+    /// Adds systems to the schedule with the given label, creating it if it doesn't exist.
+    /// Schedules are only run by the App once they've been ordered,
+    /// see [`App::add_schedule`], [`App::add_schedule_before`] and [`App::add_schedule_after`].
     pub fn add_systems<M>(
         &mut self,
-        time: ScheduleTimes,
+        label: impl ScheduleLabel,
         systems: impl IntoSystemConfigs<M>,
     ) -> &mut Self {
-        self.schedules.get_mut(&time).unwrap().add_systems(systems);
+        self.schedule_mut(label).add_systems(systems);
         self
     }
 
+    /// Runs the schedule at the end of `phase`, panics if it has already been ordered
+    pub fn add_schedule(&mut self, phase : Phase, label : impl ScheduleLabel) -> &mut Self
+    {
+        let label = label.intern();
+
+        if !self.schedule_order.push(phase, label)
+        {
+            panic!("Schedule {:?} has already been ordered", label);
+        }
+
+        self.schedule_mut(label);
+        self
+    }
+
+    /// Runs the schedule directly before `anchor`, in the same phase as `anchor`
+    pub fn add_schedule_before(&mut self, label : impl ScheduleLabel, anchor : impl ScheduleLabel) -> &mut Self
+    {
+        self.order_schedule(label, anchor, false)
+    }
+
+    /// Runs the schedule directly after `anchor`, in the same phase as `anchor`
+    pub fn add_schedule_after(&mut self, label : impl ScheduleLabel, anchor : impl ScheduleLabel) -> &mut Self
+    {
+        self.order_schedule(label, anchor, true)
+    }
+
+    fn order_schedule(&mut self, label : impl ScheduleLabel, anchor : impl ScheduleLabel, after : bool) -> &mut Self
+    {
+        let label = label.intern();
+        let anchor = anchor.intern();
+
+        if self.schedule_order.contains(label)
+        {
+            panic!("Schedule {:?} has already been ordered", label);
+        }
+
+        if !self.schedule_order.insert(label, anchor, after)
+        {
+            panic!("Can't order {:?} relative to {:?} as it isn't run by the App", label, anchor);
+        }
+
+        self.schedule_mut(label);
+        self
+    }
+
+    pub fn set_executor_kind(&mut self, label : impl ScheduleLabel, executor : ExecutorKind) -> &mut Self
+    {
+        self.schedule_mut(label).set_executor_kind(executor);
+        self
+    }
+
+    /// Gets the schedule with the given label, creating it if it doesn't exist
+    pub fn schedule_mut(&mut self, label : impl ScheduleLabel) -> &mut Schedule
+    {
+        let label = label.intern();
+        let mut schedules = self.world.resource_mut::<Schedules>();
+
+        if !schedules.contains(label)
+        {
+            schedules.insert(Schedule::new(label));
+        }
+
+        schedules.into_inner().get_mut(label).unwrap()
+    }
+
     pub fn finished_running(&self) -> bool
     {
         self.finished_running
@@ -0,0 +1,146 @@
+use std::{io, path::PathBuf};
+
+use bevy_ecs::schedule::{ExecutorKind, ScheduleLabel};
+use glium::Program;
+
+use crate::*;
+
+/// Runs right before `Update`, ticking every [`Timer`] and [`Stopwatch`] component
+#[derive(ScheduleLabel, Hash, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct TickTimers;
+
+/// Inserts [`Time`], [`FixedTime`] and the deprecated [`DeltaTime`], which the App keeps updated while they exist,
+/// and ticks [`Timer`] and [`Stopwatch`] components
+pub struct TimePlugin;
+
+impl Plugin for TimePlugin
+{
+    #[allow(deprecated)]
+    fn build(&self, app : &mut App)
+    {
+        app.world.init_resource::<Time>();
+        app.world.init_resource::<FixedTime>();
+        app.world.init_resource::<DeltaTime>();
+
+        app.add_schedule_before(TickTimers, Update)
+            .add_systems(TickTimers, tick_timers);
+    }
+}
+
+/// Runs right before `Draw`, drawing every [`Camera`] through the App's [`RenderAPI`]
+#[derive(ScheduleLabel, Hash, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Render;
+
+/// Draws every camera each frame, disable it to take over rendering
+pub struct RenderPlugin;
+
+impl Plugin for RenderPlugin
+{
+    fn build(&self, app : &mut App)
+    {
+        app.add_schedule_before(Render, Draw)
+            .set_executor_kind(Render, ExecutorKind::Simple)
+            .add_systems(Render, render_cameras);
+    }
+}
+
+/// Inserts the [`Input`] resource, which the App keeps updated with keyboard and mouse state,
+/// and registers the [`KeyboardInput`], [`MouseButtonInput`] and [`CursorMoved`] events
+pub struct InputPlugin;
+
+impl Plugin for InputPlugin
+{
+    fn build(&self, app : &mut App)
+    {
+        app.world.init_resource::<Input>();
+
+        app.add_event::<KeyboardInput>()
+            .add_event::<MouseButtonInput>()
+            .add_event::<CursorMoved>();
+    }
+}
+
+/// Runs right before `Update`, handing assets the [`AssetServer`] has finished loading to the RenderAPI
+#[derive(ScheduleLabel, Hash, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct LoadAssets;
+
+/// Inserts an [`AssetServer`] that loads meshes, shaders and glTF files from `root` or an archive,
+/// with loaders for `.obj`, `.glsl`, `.gltf` and `.glb` files, [`AssetEvent`]s for each and [`AssetLoadFailed`] events
+pub struct AssetPlugin
+{
+    pub root : PathBuf,
+    /// Read assets from this archive instead of `root`, see [`ArchiveBuilder`]
+    pub archive : Option<PathBuf>,
+    /// Reload assets when their files change, doesn't apply to archives
+    pub watch_for_changes : bool,
+    /// Cache processed meshes and shaders in this folder, see [`AssetServer::cache_processed_assets`]
+    pub processed : Option<PathBuf>,
+}
+
+impl Default for AssetPlugin
+{
+    fn default() -> Self
+    {
+        Self { root: PathBuf::from("assets"), archive: None, watch_for_changes: false, processed: None }
+    }
+}
+
+impl Plugin for AssetPlugin
+{
+    fn build(&self, app : &mut App)
+    {
+        let mut server = match &self.archive
+        {
+            // A missing or corrupt archive fails every load, each sending an AssetLoadFailed
+            Some(path) => match Archive::open(path)
+            {
+                Ok(archive) => AssetServer::with_source(archive),
+                Err(err) => AssetServer::with_source(UnavailableSource::new(io::Error::new(err.kind(),
+                    format!("Failed to open asset archive {} - {}", path.display(), err)))),
+            },
+            None => AssetServer::new(self.root.clone()),
+        };
+        if self.watch_for_changes
+        {
+            server.watch_for_changes();
+        }
+        if let Some(dir) = &self.processed
+        {
+            server.cache_processed_assets(dir.clone());
+        }
+        if let Some(context) = app.world.get_non_send_resource::<RenderContext>()
+        {
+            server.register(context.api.mesh_reserver());
+            server.register(context.api.program_reserver());
+        }
+        app.world.insert_resource(server);
+
+        app.add_event::<AssetEvent<OGLMesh>>()
+            .add_event::<AssetEvent<Program>>()
+            .add_event::<AssetLoadFailed>()
+            .add_event::<ProcessedAssetWriteFailed>()
+            .add_asset_loader(ObjLoader)
+            .add_asset_loader(GlslLoader)
+            .init_asset::<Gltf>()
+            .add_asset_loader(GltfLoader);
+
+        app.add_schedule_before(LoadAssets, Update)
+            .set_executor_kind(LoadAssets, ExecutorKind::Simple)
+            .add_systems(LoadAssets, upload_loaded_assets);
+    }
+}
+
+/// Time, input, assets and rendering
+pub struct DefaultPlugins;
+
+impl PluginGroup for DefaultPlugins
+{
+    fn build(self) -> PluginGroupBuilder
+    {
+        PluginGroupBuilder::new()
+            .add(TimePlugin)
+            .add(InputPlugin)
+            .add(AssetPlugin::default())
+            .add(RenderPlugin)
+    }
+}
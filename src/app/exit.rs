@@ -0,0 +1,68 @@
+use bevy_ecs::system::Resource;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExitReason
+{
+    /// The window's close button was pressed
+    WindowClosed,
+    /// A system called [`AppExit::request`]
+    Requested,
+}
+
+/// Tracks whether the App should shut down.
+/// Requests are acted on at the end of the frame, giving systems one frame to [`AppExit::veto`] them,
+/// after which the `End` schedule is run and GPU resources are dropped.
+#[derive(Resource, Default)]
+pub struct AppExit
+{
+    request : Option<ExitReason>,
+    vetoed : bool,
+}
+
+impl AppExit
+{
+    pub fn request(&mut self)
+    {
+        self.request_with(ExitReason::Requested);
+    }
+
+    pub(crate) fn request_with(&mut self, reason : ExitReason)
+    {
+        if self.request.is_none()
+        {
+            self.request = Some(reason);
+            self.vetoed = false;
+        }
+    }
+
+    /// Cancels the pending request, e.g. to show an unsaved changes prompt instead
+    pub fn veto(&mut self)
+    {
+        if self.request.is_some()
+        {
+            self.vetoed = true;
+        }
+    }
+
+    pub fn is_requested(&self) -> bool
+    {
+        self.request.is_some()
+    }
+
+    pub fn reason(&self) -> Option<ExitReason>
+    {
+        self.request
+    }
+
+    /// Called once per frame, returns true if the App should shut down
+    pub(crate) fn resolve(&mut self) -> bool
+    {
+        if self.vetoed
+        {
+            self.request = None;
+            self.vetoed = false;
+        }
+
+        self.request.is_some()
+    }
+}
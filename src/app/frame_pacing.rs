@@ -0,0 +1,51 @@
+use std::{sync::Arc, time::Duration};
+
+use bevy_ecs::{event::Event, system::Resource};
+
+use crate::RadError;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FrameLimit
+{
+    /// Redraw as often as possible
+    Unlimited,
+    /// Redraw at most this many times per second, sleeping in between
+    Capped(f32),
+    /// Only redraw when a window event arrives, or once `max_wait` has passed if set.
+    /// Intended for tools that would otherwise sit on a CPU core while idle.
+    LowPower { max_wait : Option<Duration> },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VSync
+{
+    Off,
+    On,
+    /// Tearing instead of stalling when a frame is late.
+    /// glutin has no swap interval for it, so the OpenGL RenderAPI refuses it.
+    Adaptive,
+}
+
+/// Sent when the RenderAPI refuses a [`FramePacing::vsync`] setting, it keeps the one it had
+#[derive(Event, Clone, Debug)]
+pub struct VSyncFailed
+{
+    pub vsync : VSync,
+    pub error : Arc<RadError>,
+}
+
+/// Controls how often the App redraws, both fields can be changed at any time.
+/// Changes to `vsync` are applied at the start of the next frame, a [`VSyncFailed`] event is sent if that fails.
+#[derive(Resource, Clone, Copy, PartialEq, Debug)]
+pub struct FramePacing
+{
+    pub limit : FrameLimit,
+    pub vsync : VSync,
+}
+
+impl Default for FramePacing
+{
+    fn default() -> Self {
+        Self { limit: FrameLimit::Capped(60.), vsync: VSync::On }
+    }
+}
@@ -1 +1,6 @@
-import!(app);
\ No newline at end of file
+import!(app);
+import!(schedule_order);
+import!(plugin);
+import!(default_plugins);
+import!(exit);
+import!(frame_pacing);
\ No newline at end of file
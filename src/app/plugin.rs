@@ -0,0 +1,169 @@
+use std::any::{type_name, Any, TypeId};
+
+use crate::App;
+
+/// A reusable collection of systems, resources and assets that can be added to an [`App`]
+pub trait Plugin: Any
+{
+    /// Configures the App this plugin is added to
+    fn build(&self, app : &mut App);
+
+    /// Used to detect duplicate plugins, defaults to the type name
+    fn name(&self) -> &str
+    {
+        type_name::<Self>()
+    }
+
+    /// Whether adding this plugin more than once is an error
+    fn is_unique(&self) -> bool
+    {
+        true
+    }
+}
+
+/// An ordered list of plugins that can be added together.
+/// Plugins in the group can be disabled or replaced before it's added to the App.
+pub trait PluginGroup
+{
+    fn build(self) -> PluginGroupBuilder;
+}
+
+pub struct PluginGroupBuilder
+{
+    plugins : Vec<(TypeId, Box<dyn Plugin>, bool)>,
+}
+
+impl Default for PluginGroupBuilder
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl PluginGroupBuilder
+{
+    pub fn new() -> Self
+    {
+        Self { plugins: vec![] }
+    }
+
+    /// Adds a plugin to the end of the group, replacing it if it's already in the group
+    pub fn add<T : Plugin>(mut self, plugin : T) -> Self
+    {
+        if let Some(entry) = self.plugins.iter_mut().find(|(id, _, _)| *id == TypeId::of::<T>())
+        {
+            entry.1 = Box::new(plugin);
+            entry.2 = true;
+        } else {
+            self.plugins.push((TypeId::of::<T>(), Box::new(plugin), true));
+        }
+        self
+    }
+
+    /// Replaces a plugin already in the group, keeping its position
+    pub fn set<T : Plugin>(self, plugin : T) -> Self
+    {
+        if !self.contains::<T>()
+        {
+            panic!("{} isn't part of this plugin group", type_name::<T>());
+        }
+        self.add(plugin)
+    }
+
+    /// Stops a plugin in the group from being built
+    pub fn disable<T : Plugin>(mut self) -> Self
+    {
+        if let Some(entry) = self.plugins.iter_mut().find(|(id, _, _)| *id == TypeId::of::<T>())
+        {
+            entry.2 = false;
+        }
+        self
+    }
+
+    pub fn enable<T : Plugin>(mut self) -> Self
+    {
+        if let Some(entry) = self.plugins.iter_mut().find(|(id, _, _)| *id == TypeId::of::<T>())
+        {
+            entry.2 = true;
+        }
+        self
+    }
+
+    pub fn contains<T : Plugin>(&self) -> bool
+    {
+        self.plugins.iter().any(|(id, _, _)| *id == TypeId::of::<T>())
+    }
+
+    pub(crate) fn finish(self, app : &mut App)
+    {
+        for (type_id, plugin, enabled) in self.plugins
+        {
+            if enabled
+            {
+                app.add_boxed_plugin(type_id, plugin);
+            }
+        }
+    }
+}
+
+impl PluginGroup for PluginGroupBuilder
+{
+    fn build(self) -> PluginGroupBuilder
+    {
+        self
+    }
+}
+
+#[doc(hidden)]
+pub struct PluginMarker;
+#[doc(hidden)]
+pub struct PluginGroupMarker;
+#[doc(hidden)]
+pub struct PluginsTupleMarker;
+
+/// Anything that can be passed to [`App::add_plugins`],
+/// a [`Plugin`], a [`PluginGroup`] or a tuple of them
+pub trait Plugins<Marker>
+{
+    fn add_to_app(self, app : &mut App);
+}
+
+impl<P : Plugin> Plugins<PluginMarker> for P
+{
+    fn add_to_app(self, app : &mut App)
+    {
+        app.add_plugin(self);
+    }
+}
+
+impl<G : PluginGroup> Plugins<PluginGroupMarker> for G
+{
+    fn add_to_app(self, app : &mut App)
+    {
+        self.build().finish(app);
+    }
+}
+
+macro_rules! impl_plugins_tuple {
+    ($(($p:ident, $m:ident)),*) => {
+        impl<$($p : Plugins<$m>, $m),*> Plugins<(PluginsTupleMarker, $($m,)*)> for ($($p,)*)
+        {
+            #[allow(non_snake_case)]
+            fn add_to_app(self, app : &mut App)
+            {
+                let ($($p,)*) = self;
+                $($p.add_to_app(app);)*
+            }
+        }
+    };
+}
+
+impl_plugins_tuple!((P0, M0));
+impl_plugins_tuple!((P0, M0), (P1, M1));
+impl_plugins_tuple!((P0, M0), (P1, M1), (P2, M2));
+impl_plugins_tuple!((P0, M0), (P1, M1), (P2, M2), (P3, M3));
+impl_plugins_tuple!((P0, M0), (P1, M1), (P2, M2), (P3, M3), (P4, M4));
+impl_plugins_tuple!((P0, M0), (P1, M1), (P2, M2), (P3, M3), (P4, M4), (P5, M5));
+impl_plugins_tuple!((P0, M0), (P1, M1), (P2, M2), (P3, M3), (P4, M4), (P5, M5), (P6, M6));
+impl_plugins_tuple!((P0, M0), (P1, M1), (P2, M2), (P3, M3), (P4, M4), (P5, M5), (P6, M6), (P7, M7));
@@ -0,0 +1,92 @@
+use bevy_ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+
+/// The part of the App's lifetime a schedule runs in
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Phase
+{
+    /// Run once before the first frame
+    Startup,
+    /// Run every fixed timestep, possibly several times per frame
+    Fixed,
+    /// Run once per frame
+    Frame,
+    /// Run once when the App shuts down
+    End,
+}
+
+/// Keeps track of which schedules the App runs and in what order
+pub(crate) struct ScheduleOrder
+{
+    phases : Vec<(Phase, Vec<InternedScheduleLabel>)>,
+}
+
+impl ScheduleOrder
+{
+    pub fn new() -> Self
+    {
+        Self
+        {
+            phases: vec![
+                (Phase::Startup, vec![]),
+                (Phase::Fixed, vec![]),
+                (Phase::Frame, vec![]),
+                (Phase::End, vec![]),
+            ]
+        }
+    }
+
+    pub fn labels(&self, phase : Phase) -> Vec<InternedScheduleLabel>
+    {
+        self.phases.iter()
+            .find(|(p, _)| *p == phase)
+            .map(|(_, labels)| labels.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn contains(&self, label : impl ScheduleLabel) -> bool
+    {
+        let label = label.intern();
+        self.phases.iter().any(|(_, labels)| labels.contains(&label))
+    }
+
+    /// Appends the label to the end of a phase.
+    /// Returns false if the label is already ordered, a schedule can only run once per phase.
+    pub fn push(&mut self, phase : Phase, label : impl ScheduleLabel) -> bool
+    {
+        let label = label.intern();
+        if self.contains(label)
+        {
+            return false;
+        }
+
+        if let Some((_, labels)) = self.phases.iter_mut().find(|(p, _)| *p == phase)
+        {
+            labels.push(label);
+        }
+        true
+    }
+
+    /// Inserts the label next to `anchor`, in whichever phase `anchor` is in.
+    /// Returns false if `anchor` isn't ordered or the label already is.
+    pub fn insert(&mut self, label : impl ScheduleLabel, anchor : impl ScheduleLabel, after : bool) -> bool
+    {
+        let label = label.intern();
+        let anchor = anchor.intern();
+
+        if self.contains(label)
+        {
+            return false;
+        }
+
+        for (_, labels) in self.phases.iter_mut()
+        {
+            if let Some(index) = labels.iter().position(|l| *l == anchor)
+            {
+                labels.insert(if after { index + 1 } else { index }, label);
+                return true;
+            }
+        }
+
+        false
+    }
+}
@@ -0,0 +1,13 @@
+use bevy_ecs::{component::Component, entity::Entity};
+
+/// The entity this one hangs off, e.g. the node above it in a glTF scene.
+/// Transforms aren't inherited, each entity's [`Transform`](crate::Transform) is already in world space.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Parent(pub Entity);
+
+/// Every entity with a [`Parent`] pointing here
+#[derive(Component, Clone, Default, PartialEq, Eq, Debug)]
+pub struct Children(pub Vec<Entity>);
+
+#[derive(Component, Clone, PartialEq, Eq, Debug)]
+pub struct Name(pub String);
@@ -1,10 +1,10 @@
 use bevy_ecs::component::Component;
 
-use crate::AssetHandle;
+use crate::{Handle, OGLMesh};
 
 #[derive(Component)]
 pub struct Mesh {
-    pub handle: AssetHandle,
+    pub handle: Handle<OGLMesh>,
 }
 
 impl Clone for Mesh
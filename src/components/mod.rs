@@ -1 +1,2 @@
-import!(mesh);
\ No newline at end of file
+import!(mesh);
+import!(hierarchy);
\ No newline at end of file
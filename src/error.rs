@@ -0,0 +1,106 @@
+use std::{fmt::Display, io};
+
+use glium::program::ShaderType;
+
+#[derive(Debug)]
+pub enum RadError
+{
+    /// Creating the vertex or index buffers for a mesh failed
+    MeshUpload(String),
+    /// A shader stage didn't compile, `log` is the GLSL compiler's info log
+    ShaderCompile { shader : ShaderType, log : String },
+    /// The shader stages compiled but didn't link, `log` is the linker's info log
+    ShaderLink { log : String },
+    /// The driver can't create the program at all, e.g. it has no shader compiler
+    ShaderUnsupported(String),
+    /// The handle's asset has been freed, or the handle came from a different `Assets`
+    InvalidHandle,
+    Io(io::Error),
+    /// A file was read but its contents couldn't be understood
+    Parse(String),
+    /// The window or its OpenGL context couldn't be created or configured
+    Window(String),
+    /// No [`AssetLoader`](crate::AssetLoader) handles the file's extension and asset type
+    NoLoader(String),
+    /// Drawing a mesh or presenting the frame failed
+    Draw(String),
+}
+
+impl Display for RadError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self
+        {
+            RadError::MeshUpload(err) => write!(f, "Failed to upload mesh - {}", err),
+            RadError::ShaderCompile { shader, log } => write!(f, "Failed to compile {:?} shader - {}", shader, log),
+            RadError::ShaderLink { log } => write!(f, "Failed to link shader program - {}", log),
+            RadError::ShaderUnsupported(err) => write!(f, "Failed to create shader program - {}", err),
+            RadError::InvalidHandle => write!(f, "Handle doesn't point to a live asset"),
+            RadError::Io(err) => write!(f, "{}", err),
+            RadError::Parse(err) => write!(f, "{}", err),
+            RadError::Window(err) => write!(f, "{}", err),
+            RadError::NoLoader(err) => write!(f, "No asset loader for {}", err),
+            RadError::Draw(err) => write!(f, "Failed to draw - {}", err),
+        }
+    }
+}
+
+impl std::error::Error for RadError
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self
+        {
+            RadError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for RadError
+{
+    fn from(err : io::Error) -> Self {
+        RadError::Io(err)
+    }
+}
+
+/// Keeps errors an [`AssetLoader`](crate::AssetLoader) passed through, anything else it couldn't understand is a parse error
+impl From<Box<dyn std::error::Error + Send + Sync>> for RadError
+{
+    fn from(err : Box<dyn std::error::Error + Send + Sync>) -> Self {
+        match err.downcast::<RadError>()
+        {
+            Ok(err) => *err,
+            Err(err) => match err.downcast::<io::Error>()
+            {
+                Ok(err) => RadError::Io(*err),
+                Err(err) => RadError::Parse(err.to_string()),
+            },
+        }
+    }
+}
+
+impl From<glium::DrawError> for RadError
+{
+    fn from(err : glium::DrawError) -> Self {
+        RadError::Draw(err.to_string())
+    }
+}
+
+impl From<glium::SwapBuffersError> for RadError
+{
+    fn from(err : glium::SwapBuffersError) -> Self {
+        RadError::Draw(err.to_string())
+    }
+}
+
+impl From<glium::ProgramCreationError> for RadError
+{
+    fn from(err : glium::ProgramCreationError) -> Self {
+        match err
+        {
+            glium::ProgramCreationError::CompilationError(log, shader) => RadError::ShaderCompile { shader, log },
+            glium::ProgramCreationError::LinkingError(log) => RadError::ShaderLink { log },
+            err => RadError::ShaderUnsupported(err.to_string()),
+        }
+    }
+}
@@ -12,4 +12,6 @@ import!(app);
 import!(ogl);
 import!(render_api);
 import!(components);
-import!(agnostic);
\ No newline at end of file
+import!(agnostic);
+import!(time);
+import!(error);
\ No newline at end of file
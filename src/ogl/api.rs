@@ -1,12 +1,22 @@
+use std::rc::Rc;
+
 use crate::{
-    ogl::OGLMesh, Assets, BakedCameraInformation, DefaultMaterial, Material, Mesh, RenderAPI, Transform
+    ogl::{OGLMesh, SharedBackend, WindowContext}, send_asset_events, Asset, Assets, BakedCameraInformation, HandleReserver, ShaderSource, WeakHandle, GLSL, DefaultMaterial, Material, Mesh, RadError, RenderAPI, Transform, VSync
 };
 use bevy_ecs::{query::QueryState, world::World};
 use glium::{
-    backend::glutin::SimpleWindowBuilder, glutin::
-        surface::WindowSurface, Depth, DepthTest, Display, DrawParameters, Program, Surface
+    backend::Context,
+    glutin::{
+        config::ConfigTemplateBuilder,
+        context::{ContextAttributesBuilder, NotCurrentGlContext},
+        display::{Display, DisplayApiPreference, GetGlDisplay, GlDisplay},
+    },
+    Depth, DepthTest, DrawParameters, Frame, Program, Surface
 };
+use glutin_winit::GlWindow;
+use raw_window_handle::{HasRawDisplayHandle, HasRawWindowHandle, RawWindowHandle};
 use winit::{
+    error::OsError,
     event_loop::{EventLoop, EventLoopBuilder},
     window::{Window, WindowBuilder},
 };
@@ -14,7 +24,8 @@ use winit::{
 const API_NAME: &str = "OpenGL4";
 
 pub struct OpenGL {
-    pub(crate) display: Display<WindowSurface>,
+    pub(crate) display: Rc<Context>,
+    window_context: Rc<WindowContext>,
     meshes: Assets<OGLMesh>,
     pub(crate) shaders: Assets<Program>,
 }
@@ -60,44 +71,6 @@ impl OpenGL {
     //     manager.step_update();
     // }
 
-    pub fn draw(&mut self, world :  &mut World, baked_camera : &BakedCameraInformation) {
-        let mut target = match baked_camera.target {
-            crate::RenderTarget::Window => self.display.draw(),
-        };
-
-        if let Some(camera_clear) = baked_camera.params.clear_colour {
-            target.clear_color_and_depth(camera_clear, 1.0);
-        } else {
-            target.clear_depth(1.0);
-        }
-
-        let mut meshes: QueryState<(&Mesh, &Transform, &DefaultMaterial)> = world.query();
-
-        for (mesh_component, transform, material) in meshes.iter(&world) {
-            let mesh = self.meshes.get_asset(&mesh_component.handle);
-
-            if mesh.is_none() {
-                continue;
-            }
-
-            let mesh = mesh.unwrap();
-
-            let result = mesh.draw(
-                &mut target,
-                transform,
-                &baked_camera,
-                material,
-                &self.shaders,
-            );
-
-            if result.is_err() {
-                println!("glium::DrawError - {}", result.unwrap_err());
-            }
-        }
-
-        target.finish().unwrap();
-    }
-
     // fn _window_event(
     //     &mut self,
     //     target: &EventLoopWindowTarget<()>,
@@ -159,28 +132,166 @@ impl OpenGL {
     //     self._frame_end()
     // }
 
-    pub fn init(window_builder: WindowBuilder) -> (Window, EventLoop<()>, Self) {
-        let event_loop = EventLoopBuilder::new().build().unwrap();
-        let (window, display) = SimpleWindowBuilder::new().set_window_builder(window_builder).build(&event_loop);
+    /// V-Sync is left to the App, which applies [`FramePacing::vsync`](crate::FramePacing::vsync) before the first frame
+    pub fn init(window_builder: WindowBuilder) -> Result<(Window, EventLoop<()>, Self), RadError> {
+        let event_loop = EventLoopBuilder::new().build()
+            .map_err(|err| RadError::Window(format!("Failed to create event loop - {}", err)))?;
+        let (window, window_context) = Self::create_window(window_builder, &event_loop)?;
 
+        let display = unsafe {
+            Context::new(SharedBackend(window_context.clone()), true, Default::default())
+        }.map_err(|err| RadError::Window(format!("Failed to load OpenGL - {}", err.0)))?;
+
+        let mut meshes = Assets::new();
         let mut shaders = Assets::new();
+        meshes.enable_events();
+        shaders.enable_events();
 
-        DefaultMaterial::glium_register(&display, &mut shaders);
+        DefaultMaterial::glium_register(&display, &mut shaders)?;
 
 
-        (window, event_loop, Self {display, meshes : Assets::new(), shaders})
+        Ok((window, event_loop, Self {display, window_context, meshes, shaders}))
+    }
+
+    /// Same as glium's SimpleWindowBuilder, but keeps the context and surface so V-Sync can be changed later.
+    /// The display is set up like glutin-winit's DisplayBuilder, whose config picker can't report that there are no configs.
+    fn create_window(window_builder: WindowBuilder, event_loop: &EventLoop<()>) -> Result<(Window, Rc<WindowContext>), RadError> {
+        let window_error = |err: OsError| RadError::Window(format!("Failed to create window - {}", err));
+
+        // WGL only offers modern OpenGL to configs picked for an existing window,
+        // everywhere else the window is created to suit the config
+        #[cfg(windows)]
+        let (mut window, window_builder) = (Some(window_builder.build(event_loop).map_err(window_error)?), None);
+        #[cfg(not(windows))]
+        let (mut window, window_builder) = (None, Some(window_builder));
+
+        let raw_window_handle = window.as_ref().map(|window: &Window| window.raw_window_handle());
+        let display = unsafe {
+            Display::new(event_loop.raw_display_handle(), Self::display_preference(raw_window_handle))
+        }.map_err(|err| RadError::Window(format!("Failed to load OpenGL - {}", err)))?;
+
+        let template = match raw_window_handle {
+            Some(raw_window_handle) => ConfigTemplateBuilder::new().compatible_with_native_window(raw_window_handle),
+            None => ConfigTemplateBuilder::new(),
+        };
+        let gl_config = unsafe { display.find_configs(template.build()) }
+            .map_err(|err| RadError::Window(format!("Failed to create window - {}", err)))?
+            .next()
+            .ok_or_else(|| RadError::Window("Failed to create window - no OpenGL config is available".to_string()))?;
+
+        if let Some(window_builder) = window_builder {
+            window = Some(glutin_winit::finalize_window(event_loop, window_builder, &gl_config).map_err(window_error)?);
+        }
+        let window = window.ok_or_else(|| RadError::Window("Failed to create window".to_string()))?;
+
+        let surface_attributes = window.build_surface_attributes(Default::default());
+        let surface = unsafe {
+            gl_config.display().create_window_surface(&gl_config, &surface_attributes)
+        }.map_err(|err| RadError::Window(format!("Failed to create window surface - {}", err)))?;
+
+        let context_attributes = ContextAttributesBuilder::new().build(Some(window.raw_window_handle()));
+        let context = unsafe {
+            gl_config.display().create_context(&gl_config, &context_attributes)
+        }.and_then(|context| context.make_current(&surface))
+            .map_err(|err| RadError::Window(format!("Failed to create OpenGL context - {}", err)))?;
+
+        Ok((window, WindowContext::new(context, surface)))
+    }
+
+    /// glutin-winit's default, falling back to EGL where there's a native API
+    fn display_preference(_raw_window_handle: Option<RawWindowHandle>) -> DisplayApiPreference {
+        #[cfg(windows)]
+        return DisplayApiPreference::WglThenEgl(_raw_window_handle);
+
+        #[cfg(target_os = "macos")]
+        return DisplayApiPreference::Cgl;
+
+        #[cfg(not(any(windows, target_os = "macos")))]
+        DisplayApiPreference::GlxThenEgl(Box::new(winit::platform::x11::register_xlib_error_hook))
     }
 }
 
 impl RenderAPI for OpenGL {
-    fn create_program(&mut self, shader : &crate::ShaderSource<crate::GLSL>)  -> crate::Shader {
-        let shader = Program::from_source(&self.display, &shader.vertex_source, &shader.fragment_source, None).unwrap();
+    fn try_create_program(&mut self, shader : &crate::ShaderSource<crate::GLSL>) -> Result<crate::Shader, RadError> {
+        let shader = Program::from_source(&self.display, &shader.vertex_source, &shader.fragment_source, None)?;
         let shader = self.shaders.add_asset(shader);
-        crate::Shader { handle: shader }
+        Ok(crate::Shader { handle: shader })
     }
 
-    fn create_mesh(&mut self, mesh_builder: crate::MeshBuilder) -> crate::Mesh {
-        let mesh = OGLMesh::new(&self, mesh_builder).unwrap();
-        crate::Mesh { handle:  self.meshes.add_asset(mesh)}
+    fn try_create_mesh(&mut self, mesh_builder: crate::MeshBuilder) -> Result<crate::Mesh, RadError> {
+        let mesh = OGLMesh::new(&self, mesh_builder)?;
+        Ok(crate::Mesh { handle:  self.meshes.add_asset(mesh)})
+    }
+
+    fn mesh_reserver(&self) -> HandleReserver<OGLMesh> {
+        self.meshes.reserver()
+    }
+
+    fn upload_mesh(&mut self, handle : WeakHandle<OGLMesh>, mesh_builder : crate::MeshBuilder) -> Result<(), RadError> {
+        let mesh = OGLMesh::new(&self, mesh_builder)?;
+        self.meshes.insert(handle, mesh).map(|_| ()).map_err(|_| RadError::InvalidHandle)
+    }
+
+    fn program_reserver(&self) -> HandleReserver<Program> {
+        self.shaders.reserver()
+    }
+
+    fn upload_program(&mut self, handle : WeakHandle<Program>, shader : &crate::ShaderSource<crate::GLSL>) -> Result<(), RadError> {
+        let program = Program::from_source(&self.display, &shader.vertex_source, &shader.fragment_source, None)?;
+        self.shaders.insert(handle, program).map(|_| ()).map_err(|_| RadError::InvalidHandle)
+    }
+
+    fn draw(&mut self, world :  &mut World, baked_camera : &BakedCameraInformation) -> Result<(), RadError> {
+        let mut target = match baked_camera.target {
+            crate::RenderTarget::Window => Frame::new(self.display.clone(), self.display.get_framebuffer_dimensions()),
+        };
+
+        if let Some(camera_clear) = baked_camera.params.clear_colour {
+            target.clear_color_and_depth(camera_clear, 1.0);
+        } else {
+            target.clear_depth(1.0);
+        }
+
+        let mut meshes: QueryState<(&Mesh, &Transform, &DefaultMaterial)> = world.query();
+        let mut failed = None;
+
+        for (mesh_component, transform, material) in meshes.iter(&world) {
+            let Some(mesh) = self.meshes.get_asset(&mesh_component.handle) else {
+                continue;
+            };
+
+            // The other meshes are still drawn, only the first failure is reported
+            if let Err(err) = mesh.draw(&mut target, transform, baked_camera, material, &self.shaders) {
+                failed.get_or_insert(err);
+            }
+        }
+
+        // The frame has to be finished even if drawing failed
+        let finished = target.finish();
+
+        match failed {
+            Some(err) => Err(err),
+            None => Ok(finished?),
+        }
+    }
+
+    fn set_vsync(&mut self, vsync: VSync) -> Result<(), RadError> {
+        self.window_context.set_vsync(vsync)
+    }
+
+    fn cleanup(&mut self, world: &mut World) {
+        self.meshes.cleanup();
+        self.shaders.cleanup();
+
+        send_asset_events(world, self.meshes.drain_events());
+        send_asset_events(world, self.shaders.drain_events());
+    }
+}
+
+impl Asset for Program {
+    type Loaded = ShaderSource<GLSL>;
+
+    fn upload(api: &mut dyn RenderAPI, _world: &mut World, handle: WeakHandle<Self>, loaded: ShaderSource<GLSL>) -> Result<(), RadError> {
+        api.upload_program(handle, &loaded)
     }
 }
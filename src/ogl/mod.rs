@@ -1,4 +1,5 @@
 import!(api);
 import!(ogl_mesh);
 import!(systems);
-import!(types);
\ No newline at end of file
+import!(types);
+import!(window_context);
\ No newline at end of file
@@ -1,38 +1,63 @@
-use glium::{index::PrimitiveType, Frame, Program};
+use bevy_ecs::world::World;
+use glium::{index::{IndexBufferAny, PrimitiveType}, Frame, IndexBuffer, Program};
 
-use crate::{Assets, BakedCameraInformation, DefaultMaterial, MeshBuilder, OpenGL, Transform, Vertex};
+use crate::{Asset, Assets, BakedCameraInformation, DefaultMaterial, Indices, MeshBuilder, OpenGL, RadError, RenderAPI, Tangent, Transform, Vertex, WeakHandle};
 
-pub(super) struct OGLMesh {
+/// The GPU side of a Mesh, what `Mesh::handle` points to when rendering with OpenGL
+pub struct OGLMesh {
     vertex_buffer: glium::VertexBuffer<Vertex>,
-    index_buffer: glium::IndexBuffer<u16>,
+    /// Only for meshes built with tangents, bound alongside `vertex_buffer`
+    tangent_buffer: Option<glium::VertexBuffer<Tangent>>,
+    /// u16 or u32 depending on the builder's [`Indices`]
+    index_buffer: IndexBufferAny,
 }
 
 impl OGLMesh {
-    pub fn new(gl_context: &OpenGL, builder: MeshBuilder) -> Result<Self, ()> {
-        let vertex_buffer = glium::VertexBuffer::new(&gl_context.display, &builder.vertices);
-        if vertex_buffer.is_err() {
-            return Err(());
-        }
-        let vertex_buffer = vertex_buffer.unwrap();
-
-        let index_buffer = glium::IndexBuffer::new(
-            &gl_context.display,
-            PrimitiveType::TrianglesList,
-            &builder.indices.unwrap_or((0..builder.vertices.len()).map(|v| v as u16).collect::<Vec<u16>>()),
-        );
-        if index_buffer.is_err() {
-            return Err(());
-        }
-        let index_buffer = index_buffer.unwrap();
+    pub fn new(gl_context: &OpenGL, builder: MeshBuilder) -> Result<Self, RadError> {
+        builder.validate()?;
+
+        let vertex_buffer = glium::VertexBuffer::new(&gl_context.display, &builder.vertices)
+            .map_err(|err| RadError::MeshUpload(format!("vertex buffer: {}", err)))?;
+
+        let tangent_buffer = match &builder.tangents {
+            Some(tangents) => {
+                let tangents: Vec<Tangent> = tangents.iter().map(|tangent| Tangent { tangent: *tangent }).collect();
+                Some(glium::VertexBuffer::new(&gl_context.display, &tangents)
+                    .map_err(|err| RadError::MeshUpload(format!("tangent buffer: {}", err)))?)
+            },
+            None => None,
+        };
+
+        let indices = match builder.indices {
+            Some(indices) => indices,
+            None => Indices::sequential(builder.vertices.len())?,
+        };
+
+        let index_buffer = match &indices {
+            Indices::U16(indices) => IndexBuffer::new(&gl_context.display, PrimitiveType::TrianglesList, indices).map(IndexBufferAny::from),
+            Indices::U32(indices) => IndexBuffer::new(&gl_context.display, PrimitiveType::TrianglesList, indices).map(IndexBufferAny::from),
+        }.map_err(|err| RadError::MeshUpload(format!("index buffer: {}", err)))?;
 
         Ok(Self {
             vertex_buffer,
+            tangent_buffer,
             index_buffer,
         })
     }
 
-    pub fn draw(&self, context : &mut Frame, transform : &Transform, baked_camera : &BakedCameraInformation, material : &DefaultMaterial, programs : &Assets<Program>) -> Result<(), glium::DrawError>
+    pub fn draw(&self, context : &mut Frame, transform : &Transform, baked_camera : &BakedCameraInformation, material : &DefaultMaterial, programs : &Assets<Program>) -> Result<(), RadError>
     {
-        material.draw_glium(context, Some(transform), &baked_camera, (&self.vertex_buffer, &self.index_buffer), programs)
+        match &self.tangent_buffer {
+            Some(tangent_buffer) => material.draw_glium(context, Some(transform), baked_camera, ((&self.vertex_buffer, tangent_buffer), &self.index_buffer), programs),
+            None => material.draw_glium(context, Some(transform), baked_camera, (&self.vertex_buffer, &self.index_buffer), programs),
+        }
+    }
+}
+
+impl Asset for OGLMesh {
+    type Loaded = MeshBuilder;
+
+    fn upload(api: &mut dyn RenderAPI, _world: &mut World, handle: WeakHandle<Self>, loaded: MeshBuilder) -> Result<(), RadError> {
+        api.upload_mesh(handle, loaded)
     }
 }
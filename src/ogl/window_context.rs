@@ -0,0 +1,74 @@
+use std::{ffi::{c_void, CString}, num::NonZeroU32, ptr, rc::Rc};
+
+use glium::{
+    backend::Backend,
+    glutin::{
+        context::{PossiblyCurrentContext, PossiblyCurrentGlContext},
+        display::{GetGlDisplay, GlDisplay},
+        surface::{GlSurface, Surface, SwapInterval, WindowSurface},
+    },
+    SwapBuffersError,
+};
+
+use crate::{RadError, VSync};
+
+/// The window's GL context and surface.
+/// glium's Display takes ownership of both, so this is handed to glium as a [`Backend`] instead
+/// and kept around for settings that can change after the window is created.
+pub(crate) struct WindowContext {
+    context: PossiblyCurrentContext,
+    surface: Surface<WindowSurface>,
+}
+
+impl WindowContext {
+    pub fn new(context: PossiblyCurrentContext, surface: Surface<WindowSurface>) -> Rc<Self> {
+        Rc::new(Self { context, surface })
+    }
+
+    pub fn set_vsync(&self, vsync: VSync) -> Result<(), RadError> {
+        let interval = match vsync {
+            VSync::Off => SwapInterval::DontWait,
+            VSync::On => SwapInterval::Wait(NonZeroU32::MIN),
+            VSync::Adaptive => return Err(RadError::Window("Failed to set V-Sync - glutin doesn't support adaptive V-Sync".to_string())),
+        };
+
+        self.surface.set_swap_interval(&self.context, interval)
+            .map_err(|err| RadError::Window(format!("Failed to set V-Sync - {}", err)))
+    }
+}
+
+/// Shares a [`WindowContext`] with glium
+pub(crate) struct SharedBackend(pub Rc<WindowContext>);
+
+unsafe impl Backend for SharedBackend {
+    fn swap_buffers(&self) -> Result<(), SwapBuffersError> {
+        self.0.surface.swap_buffers(&self.0.context).map_err(|_| SwapBuffersError::ContextLost)
+    }
+
+    unsafe fn get_proc_address(&self, symbol: &str) -> *const c_void {
+        match CString::new(symbol) {
+            Ok(symbol) => self.0.context.display().get_proc_address(&symbol),
+            Err(_) => ptr::null(),
+        }
+    }
+
+    fn get_framebuffer_dimensions(&self) -> (u32, u32) {
+        (self.0.surface.width().unwrap_or(0), self.0.surface.height().unwrap_or(0))
+    }
+
+    fn resize(&self, (width, height): (u32, u32)) {
+        // Minimised windows report a zero size, the surface keeps its old one until they're restored
+        if let (Some(width), Some(height)) = (NonZeroU32::new(width), NonZeroU32::new(height)) {
+            self.0.surface.resize(&self.0.context, width, height);
+        }
+    }
+
+    fn is_current(&self) -> bool {
+        self.0.context.is_current()
+    }
+
+    unsafe fn make_current(&self) {
+        // glium has no way to hear about a failure here, its next GL call reports the lost context instead
+        let _ = self.0.context.make_current(&self.0.surface);
+    }
+}
@@ -0,0 +1,323 @@
+use std::{collections::HashMap, fs::File, io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write}, path::Path, sync::Mutex};
+
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+
+use crate::AssetSource;
+
+// Layout, all integers little endian:
+//   magic "RADPACK\0", version u32, entry count u32
+//   per entry: path length u16, path (utf-8), offset u64, stored length u64, length u64, compression u8, hash u64
+//   file data, offsets are from the start of the archive
+
+const MAGIC : &[u8; 8] = b"RADPACK\0";
+const VERSION : u32 = 1;
+
+const STORED : u8 = 0;
+const DEFLATE : u8 = 1;
+
+const HEADER_LEN : u64 = 8 + 4 + 4;
+/// Index entry size without the path
+const ENTRY_LEN : u64 = 2 + 8 * 4 + 1;
+/// DEFLATE can't shrink data by more than this, anything claiming more is corrupt
+const MAX_DEFLATE_RATIO : u64 = 1032;
+
+/// 64 bit FNV-1a, stable across builds so it can be written to disk
+pub fn content_hash(bytes : &[u8]) -> u64
+{
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3))
+}
+
+fn invalid_data(message : String) -> io::Error
+{
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+pub(crate) fn read_u8(reader : &mut impl Read) -> io::Result<u8>
+{
+    let mut bytes = [0; 1];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes[0])
+}
+
+pub(crate) fn read_u16(reader : &mut impl Read) -> io::Result<u16>
+{
+    let mut bytes = [0; 2];
+    reader.read_exact(&mut bytes)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+pub(crate) fn read_u32(reader : &mut impl Read) -> io::Result<u32>
+{
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+pub(crate) fn read_u64(reader : &mut impl Read) -> io::Result<u64>
+{
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+#[derive(Clone, Debug)]
+pub struct ArchiveEntry
+{
+    offset : u64,
+    stored_len : u64,
+    pub len : u64,
+    pub compressed : bool,
+    /// [`content_hash`] of the uncompressed file
+    pub hash : u64,
+}
+
+/// A packed archive opened for reading, only the index is kept in memory
+pub struct Archive
+{
+    file : Mutex<BufReader<File>>,
+    entries : HashMap<String, ArchiveEntry>,
+}
+
+impl Archive
+{
+    pub fn open(path : impl AsRef<Path>) -> io::Result<Self>
+    {
+        let file = File::open(path)?;
+        let file_len = file.metadata()?.len();
+        let mut file = BufReader::new(file);
+
+        let mut magic = [0; 8];
+        file.read_exact(&mut magic)?;
+        if &magic != MAGIC
+        {
+            return Err(invalid_data(String::from("Not an asset archive")))
+        }
+
+        let version = read_u32(&mut file)?;
+        if version != VERSION
+        {
+            return Err(invalid_data(format!("Unsupported archive version {}", version)))
+        }
+
+        // Checked before allocating so a corrupt count can't ask for more than the file could hold
+        let count = read_u32(&mut file)?;
+        if (count as u64) * ENTRY_LEN > file_len.saturating_sub(HEADER_LEN)
+        {
+            return Err(invalid_data(format!("Archive index claims {} entries, more than fit in the file", count)))
+        }
+        let mut entries = HashMap::with_capacity(count as usize);
+
+        for _ in 0..count
+        {
+            let mut path = vec![0; read_u16(&mut file)? as usize];
+            file.read_exact(&mut path)?;
+            let path = String::from_utf8(path).map_err(|_| invalid_data(String::from("Archive path isn't utf-8")))?;
+
+            let offset = read_u64(&mut file)?;
+            let stored_len = read_u64(&mut file)?;
+            let len = read_u64(&mut file)?;
+            let compression = read_u8(&mut file)?;
+            let hash = read_u64(&mut file)?;
+
+            if offset.checked_add(stored_len).filter(|end| *end <= file_len).is_none()
+            {
+                return Err(invalid_data(format!("{} points past the end of the archive", path)))
+            }
+
+            let valid_len = match compression
+            {
+                STORED => len == stored_len,
+                DEFLATE => len <= stored_len.saturating_mul(MAX_DEFLATE_RATIO),
+                _ => return Err(invalid_data(format!("{} has an unknown compression {}", path, compression))),
+            };
+            if !valid_len
+            {
+                return Err(invalid_data(format!("{} claims {} bytes from {} stored", path, len, stored_len)))
+            }
+
+            entries.insert(path, ArchiveEntry { offset, stored_len, len, compressed: compression == DEFLATE, hash });
+        }
+
+        Ok(Self { file: Mutex::new(file), entries })
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &ArchiveEntry)>
+    {
+        self.entries.iter().map(|(path, entry)| (path.as_str(), entry))
+    }
+
+    pub fn contains(&self, path : &str) -> bool
+    {
+        self.entries.contains_key(path)
+    }
+
+    /// Reads and decompresses a file, checking it against its content hash
+    pub fn read(&self, path : &str) -> io::Result<Vec<u8>>
+    {
+        let entry = self.entries.get(path)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{} isn't in the archive", path)))?;
+
+        let mut stored = vec![0; entry.stored_len as usize];
+        {
+            let mut file = self.file.lock().unwrap();
+            file.seek(SeekFrom::Start(entry.offset))?;
+            file.read_exact(&mut stored)?;
+        }
+
+        let bytes = if entry.compressed
+        {
+            // One byte past the expected length is enough to tell it's corrupt
+            let mut bytes = Vec::with_capacity(entry.len as usize);
+            DeflateDecoder::new(stored.as_slice()).take(entry.len + 1).read_to_end(&mut bytes)?;
+            bytes
+        } else {
+            stored
+        };
+
+        if bytes.len() as u64 != entry.len || content_hash(&bytes) != entry.hash
+        {
+            return Err(invalid_data(format!("{} is corrupted", path)))
+        }
+
+        Ok(bytes)
+    }
+}
+
+impl AssetSource for Archive
+{
+    fn read(&self, path : &str) -> io::Result<Vec<u8>>
+    {
+        Archive::read(self, path)
+    }
+}
+
+/// Packs files into an [`Archive`]
+pub struct ArchiveBuilder
+{
+    files : Vec<(String, Vec<u8>)>,
+    compress : bool,
+}
+
+impl Default for ArchiveBuilder
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl ArchiveBuilder
+{
+    /// Files are compressed unless [`ArchiveBuilder::compress`] turns it off
+    pub fn new() -> Self
+    {
+        Self { files: vec![], compress: true }
+    }
+
+    /// Compresses each file, files that don't get any smaller are stored as they are
+    pub fn compress(mut self, compress : bool) -> Self
+    {
+        self.compress = compress;
+        self
+    }
+
+    /// Adds a file, replacing any already added at `path`
+    pub fn add_file(mut self, path : impl Into<String>, bytes : Vec<u8>) -> Self
+    {
+        let path = path.into().replace('\\', "/");
+        self.files.retain(|(existing, _)| *existing != path);
+        self.files.push((path, bytes));
+        self
+    }
+
+    /// Adds every file under `dir`, with paths relative to it
+    pub fn add_dir(mut self, dir : impl AsRef<Path>) -> io::Result<Self>
+    {
+        let dir = dir.as_ref();
+        let mut pending = vec![dir.to_path_buf()];
+
+        while let Some(current) = pending.pop()
+        {
+            for entry in std::fs::read_dir(&current)?
+            {
+                let path = entry?.path();
+
+                if path.is_dir()
+                {
+                    pending.push(path);
+                    continue;
+                }
+
+                let relative = path.strip_prefix(dir).unwrap().components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                self = self.add_file(relative, std::fs::read(&path)?);
+            }
+        }
+
+        Ok(self)
+    }
+
+    pub fn write(self, path : impl AsRef<Path>) -> io::Result<()>
+    {
+        let mut file = BufWriter::new(File::create(path)?);
+        self.write_to(&mut file)?;
+        file.flush()
+    }
+
+    pub fn write_to(mut self, writer : &mut impl Write) -> io::Result<()>
+    {
+        // Sorted so the same files always pack into the same archive
+        self.files.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut stored = Vec::with_capacity(self.files.len());
+        for (path, bytes) in &self.files
+        {
+            if path.len() > u16::MAX as usize
+            {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} is too long a path", path)))
+            }
+
+            let compressed = if self.compress
+            {
+                let mut encoder = DeflateEncoder::new(vec![], Compression::default());
+                encoder.write_all(bytes)?;
+                Some(encoder.finish()?).filter(|compressed| compressed.len() < bytes.len())
+            } else {
+                None
+            };
+
+            stored.push(compressed);
+        }
+
+        let index_len : u64 = self.files.iter().map(|(path, _)| ENTRY_LEN + path.len() as u64).sum();
+        let mut offset = HEADER_LEN + index_len;
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&(self.files.len() as u32).to_le_bytes())?;
+
+        for ((path, bytes), compressed) in self.files.iter().zip(&stored)
+        {
+            let stored_len = compressed.as_ref().map_or(bytes.len(), Vec::len) as u64;
+
+            writer.write_all(&(path.len() as u16).to_le_bytes())?;
+            writer.write_all(path.as_bytes())?;
+            writer.write_all(&offset.to_le_bytes())?;
+            writer.write_all(&stored_len.to_le_bytes())?;
+            writer.write_all(&(bytes.len() as u64).to_le_bytes())?;
+            writer.write_all(&[if compressed.is_some() { DEFLATE } else { STORED }])?;
+            writer.write_all(&content_hash(bytes).to_le_bytes())?;
+
+            offset += stored_len;
+        }
+
+        for ((_, bytes), compressed) in self.files.iter().zip(&stored)
+        {
+            writer.write_all(compressed.as_ref().unwrap_or(bytes))?;
+        }
+
+        Ok(())
+    }
+}
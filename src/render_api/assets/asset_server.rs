@@ -0,0 +1,697 @@
+use std::{any::{type_name, Any, TypeId}, collections::HashMap, error::Error, io, panic::{catch_unwind, AssertUnwindSafe}, path::{Path, PathBuf}, sync::{mpsc::{channel, Receiver, Sender}, Arc, Mutex, RwLock}, thread, time::Duration};
+
+use bevy_ecs::{event::{Event, Events}, system::Resource, world::{Mut, World}};
+
+use crate::{AssetId, Assets, AssetSource, FileSource, FileWatcher, Handle, HandleReserver, ProcessedCache, RadError, RenderAPI, RenderContext, WeakHandle};
+
+/// An asset type the [`AssetServer`] can hand out handles for
+pub trait Asset : Sized + 'static
+{
+    /// What an [`AssetLoader`] produces on the loading thread, before it's turned into the asset
+    type Loaded : Send + 'static;
+
+    /// Called on the main thread to create the asset behind `handle`.
+    /// Assets that don't live in the RenderAPI can implement [`CpuAsset`] instead.
+    fn upload(api : &mut dyn RenderAPI, world : &mut World, handle : WeakHandle<Self>, loaded : Self::Loaded) -> Result<(), RadError>;
+}
+
+/// An asset that doesn't live in the RenderAPI. Its loader returns the asset itself, which is put in the `Assets<Self>`
+/// resource added by [`App::init_asset`](crate::App::init_asset).
+pub trait CpuAsset : Send + Sync + 'static {}
+
+impl<T : CpuAsset> Asset for T
+{
+    type Loaded = T;
+
+    fn upload(_api : &mut dyn RenderAPI, world : &mut World, handle : WeakHandle<Self>, loaded : T) -> Result<(), RadError>
+    {
+        world.resource_mut::<Assets<T>>().insert(handle, loaded).map(|_| ()).map_err(|_| RadError::InvalidHandle)
+    }
+}
+
+/// Turns the bytes of a file into an asset, registered with [`App::add_asset_loader`](crate::App::add_asset_loader).
+/// Runs on the loading thread.
+pub trait AssetLoader : Send + Sync + 'static
+{
+    type Asset : Asset;
+
+    /// File extensions this loader handles, without the dot
+    fn extensions(&self) -> &[&str];
+
+    fn load(&self, bytes : Vec<u8>, context : &mut LoadContext) -> Result<<Self::Asset as Asset>::Loaded, Box<dyn Error + Send + Sync>>;
+
+    /// Hashed into the key of every asset this loader processes, change it when a setting changes what `load` returns
+    fn settings_hash(&self) -> u64
+    {
+        0
+    }
+
+    /// Names and versions what [`AssetLoader::process`] writes, e.g. `"rad.mesh.1"`, and is hashed into every processed asset's key.
+    /// Change it whenever the processed form changes. Loaders that return None are never cached.
+    fn processed_format(&self) -> Option<&str>
+    {
+        None
+    }
+
+    /// Turns a freshly loaded asset into bytes [`AssetLoader::load_processed`] can read back faster than loading the source.
+    /// Only used once [`AssetServer::cache_processed_assets`] is on and the loader has a [`AssetLoader::processed_format`],
+    /// loaders that return None always load the source.
+    fn process(&self, _loaded : &<Self::Asset as Asset>::Loaded) -> Option<Vec<u8>>
+    {
+        None
+    }
+
+    fn load_processed(&self, _bytes : Vec<u8>) -> Result<<Self::Asset as Asset>::Loaded, Box<dyn Error + Send + Sync>>
+    {
+        Err(format!("{} doesn't process assets", type_name::<Self>()).into())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum LoadState
+{
+    /// The AssetServer doesn't know about the handle, e.g. it was made with `Assets::add_asset`
+    NotLoaded,
+    Loading,
+    Loaded,
+    /// Shared with every sub-asset that failed because of it
+    Failed(Arc<RadError>),
+}
+
+/// Failures are equal when their errors read the same
+impl PartialEq for LoadState
+{
+    fn eq(&self, other : &Self) -> bool
+    {
+        match (self, other)
+        {
+            (LoadState::Failed(a), LoadState::Failed(b)) => Arc::ptr_eq(a, b) || a.to_string() == b.to_string(),
+            (a, b) => std::mem::discriminant(a) == std::mem::discriminant(b),
+        }
+    }
+}
+
+impl Eq for LoadState {}
+
+/// Sent whenever a file fails to load or reload.
+/// A failed first load also shows up in [`AssetServer::load_state`], a failed reload leaves the previous asset in place
+/// and is only reported here.
+#[derive(Event, Clone, Debug)]
+pub struct AssetLoadFailed
+{
+    /// The file being loaded, relative to the source
+    pub path : String,
+    pub reload : bool,
+    pub error : Arc<RadError>,
+}
+
+/// Sent when an asset loaded but its processed form couldn't be written to the cache, see [`AssetServer::cache_processed_assets`].
+/// The asset is loaded from source again next time.
+#[derive(Event, Clone, Debug)]
+pub struct ProcessedAssetWriteFailed
+{
+    /// The source file, relative to the source
+    pub path : String,
+    pub error : Arc<RadError>,
+}
+
+/// Lets the loading thread check and reserve handles without knowing their type
+trait ErasedReserver : Send + Sync
+{
+    fn as_any(&self) -> &dyn Any;
+    fn is_alive(&self, id : AssetId) -> bool;
+}
+
+impl<T : 'static> ErasedReserver for HandleReserver<T>
+{
+    fn as_any(&self) -> &dyn Any
+    {
+        self
+    }
+
+    fn is_alive(&self, id : AssetId) -> bool
+    {
+        WeakHandle::<T>::from_id(id).is_some_and(|handle| HandleReserver::is_alive(self, handle))
+    }
+}
+
+/// State shared with the loading thread
+#[derive(Default)]
+struct Shared
+{
+    reservers : RwLock<HashMap<TypeId, Box<dyn ErasedReserver>>>,
+    /// Every path handed out, including `path#label` for sub-assets
+    paths : Mutex<HashMap<(TypeId, String), AssetId>>,
+    processed : RwLock<Option<ProcessedCache>>,
+    /// Cache writes that failed on the loading thread, sent as events on the main thread
+    write_failures : Mutex<Vec<ProcessedAssetWriteFailed>>,
+}
+
+impl Shared
+{
+    /// Gets the live handle for `path`, or reserves a new one. Also returns whether it's new.
+    fn handle_for<T : Asset>(&self, path : &str) -> (Handle<T>, bool)
+    {
+        let reservers = self.reservers.read().unwrap();
+        let reserver = reservers.get(&TypeId::of::<T>())
+            .and_then(|reserver| reserver.as_any().downcast_ref::<HandleReserver<T>>())
+            .unwrap_or_else(|| panic!("{} hasn't been registered with the AssetServer", type_name::<T>()));
+
+        let mut paths = self.paths.lock().unwrap();
+        let key = (TypeId::of::<T>(), path.to_string());
+
+        if let Some(handle) = paths.get(&key)
+            .and_then(|id| WeakHandle::<T>::from_id(*id))
+            .and_then(|weak| reserver.upgrade(weak))
+        {
+            return (handle, false)
+        }
+
+        let handle = reserver.reserve();
+        paths.insert(key, handle.downgrade().id());
+        (handle, true)
+    }
+
+    fn is_alive(&self, id : AssetId) -> bool
+    {
+        self.reservers.read().unwrap().values().any(|reserver| reserver.is_alive(id))
+    }
+
+    /// Drops the paths of assets that have been freed, locking in the same order as `handle_for`
+    fn forget_freed(&self)
+    {
+        let reservers = self.reservers.read().unwrap();
+        self.paths.lock().unwrap().retain(|_, id| reservers.values().any(|reserver| reserver.is_alive(*id)));
+    }
+}
+
+/// A finished asset waiting to be handed to the RenderAPI on the main thread
+struct PendingUpload
+{
+    id : AssetId,
+    run : Box<dyn FnOnce(&mut dyn RenderAPI, &mut World) -> Result<(), RadError> + Send>,
+}
+
+impl PendingUpload
+{
+    fn new<T : Asset>(handle : WeakHandle<T>, loaded : T::Loaded) -> Self
+    {
+        let run = Box::new(move |api : &mut dyn RenderAPI, world : &mut World| T::upload(api, world, handle, loaded));
+        Self { id: handle.id(), run }
+    }
+}
+
+/// Given to an [`AssetLoader`] while it loads a file
+pub struct LoadContext<'a>
+{
+    path : &'a str,
+    source : &'a dyn AssetSource,
+    shared : &'a Shared,
+    uploads : Vec<PendingUpload>,
+}
+
+impl<'a> LoadContext<'a>
+{
+    /// The file being loaded
+    pub fn path(&self) -> &Path
+    {
+        Path::new(self.path)
+    }
+
+    /// Reads another file from the same source, e.g. a buffer a model refers to.
+    /// `relative` is relative to the folder of the file being loaded and can't climb out of the source.
+    /// Changes to it don't trigger a hot reload.
+    pub fn read(&self, relative : &str) -> io::Result<Vec<u8>>
+    {
+        let mut segments : Vec<&str> = self.path.split('/').collect();
+        segments.pop();
+
+        for segment in relative.split('/')
+        {
+            match segment
+            {
+                "" | "." => (),
+                ".." => { segments.pop(); },
+                segment => segments.push(segment),
+            }
+        }
+
+        let path = segments.join("/");
+        self.source.read(&path).map_err(|err| io::Error::new(err.kind(), format!("{}: {}", path, err)))
+    }
+
+    /// Adds an asset that comes out of the same file, loadable on its own as `path#label`.
+    /// Sub-assets are created before the asset the loader returns, so it can hold on to their handles.
+    pub fn add_labeled_asset<T : Asset>(&mut self, label : &str, loaded : T::Loaded) -> Handle<T>
+    {
+        let (handle, _) = self.shared.handle_for::<T>(&format!("{}#{}", self.path, label));
+        self.uploads.push(PendingUpload::new(handle.downgrade(), loaded));
+        handle
+    }
+}
+
+/// An [`AssetLoader`] with its asset type hidden so loaders for different types can be stored together
+trait ErasedLoader : Send + Sync
+{
+    fn asset_type(&self) -> TypeId;
+    fn extensions(&self) -> &[&str];
+    /// Queues the loaded asset for `root` after any sub-assets, `root` is None when only sub-assets were asked for
+    fn load(&self, bytes : Vec<u8>, context : &mut LoadContext, root : Option<AssetId>) -> Result<(), RadError>;
+}
+
+impl<L : AssetLoader> ErasedLoader for L
+{
+    fn asset_type(&self) -> TypeId
+    {
+        TypeId::of::<L::Asset>()
+    }
+
+    fn extensions(&self) -> &[&str]
+    {
+        AssetLoader::extensions(self)
+    }
+
+    fn load(&self, bytes : Vec<u8>, context : &mut LoadContext, root : Option<AssetId>) -> Result<(), RadError>
+    {
+        let Some(handle) = root.and_then(WeakHandle::<L::Asset>::from_id) else {
+            // Only sub-assets were asked for, and those aren't processed
+            return AssetLoader::load(self, bytes, context).map(|_| ()).map_err(RadError::from)
+        };
+
+        // Cloned so loading doesn't hold the lock
+        let shared = context.shared;
+        let processed = self.processed_format().and_then(|format| Some((shared.processed.read().unwrap().clone()?, format)));
+        let cache = processed.as_ref().map(|(cache, format)| (cache, ProcessedCache::key(&bytes, format, self.settings_hash())));
+
+        // An unreadable processed file is treated as out of date and replaced
+        let cached = cache
+            .and_then(|(cache, key)| cache.read(context.path, key))
+            .and_then(|processed| self.load_processed(processed).ok());
+
+        let loaded = match cached
+        {
+            Some(loaded) => loaded,
+            None => {
+                let loaded = AssetLoader::load(self, bytes, context)?;
+
+                // Sub-assets can't be rebuilt from the processed form, so files with any are always loaded from source
+                if let (Some((cache, key)), true) = (cache, context.uploads.is_empty())
+                {
+                    if let Some(Err(err)) = self.process(&loaded).map(|processed| cache.write(context.path, key, &processed))
+                    {
+                        let failure = ProcessedAssetWriteFailed { path: context.path.to_string(), error: Arc::new(err.into()) };
+                        shared.write_failures.lock().unwrap().push(failure);
+                    }
+                }
+
+                loaded
+            },
+        };
+
+        context.uploads.push(PendingUpload::new(handle, loaded));
+        Ok(())
+    }
+}
+
+struct Job
+{
+    file : String,
+    root : Option<AssetId>,
+    loader : Arc<dyn ErasedLoader>,
+    reload : bool,
+}
+
+struct Completed
+{
+    file : String,
+    root : Option<AssetId>,
+    reload : bool,
+    result : Result<Vec<PendingUpload>, RadError>,
+}
+
+fn run_job(source : &dyn AssetSource, shared : &Shared, job : &Job) -> Result<Vec<PendingUpload>, RadError>
+{
+    let bytes = source.read(&job.file).map_err(|err| io::Error::new(err.kind(), format!("{}: {}", job.file, err)))?;
+
+    let mut context = LoadContext { path: &job.file, source, shared, uploads: vec![] };
+    job.loader.load(bytes, &mut context, job.root)?;
+
+    Ok(context.uploads)
+}
+
+/// A file being watched for changes, and what to reload when it does
+struct Watched
+{
+    root : Option<AssetId>,
+    loader : Arc<dyn ErasedLoader>,
+}
+
+/// Loads assets from an [`AssetSource`] on a background thread, using the [`AssetLoader`] registered for the file's extension.
+/// Handles are given out straight away and point to nothing until the asset has loaded.
+#[derive(Resource)]
+pub struct AssetServer
+{
+    source : Arc<dyn AssetSource>,
+    shared : Arc<Shared>,
+    loaders : Vec<Arc<dyn ErasedLoader>>,
+    states : Mutex<HashMap<AssetId, LoadState>>,
+    /// How many first loads of each file are still running, sub-assets asked for meanwhile come out of those
+    loading_files : Mutex<HashMap<String, usize>>,
+    /// Loads that failed before reaching the loading thread, sent as events with the others
+    failed_loads : Mutex<Vec<AssetLoadFailed>>,
+    jobs : Sender<Job>,
+    completed : Mutex<Receiver<Completed>>,
+    watcher : Option<FileWatcher>,
+    watched : Mutex<HashMap<String, Watched>>,
+}
+
+impl AssetServer
+{
+    /// Loads loose files from the `root` folder
+    pub fn new(root : impl Into<PathBuf>) -> Self
+    {
+        Self::with_source(FileSource::new(root))
+    }
+
+    pub fn with_source(source : impl AssetSource) -> Self
+    {
+        let source : Arc<dyn AssetSource> = Arc::new(source);
+        let shared = Arc::new(Shared::default());
+        let (jobs, job_receiver) = channel::<Job>();
+        let (completed_sender, completed) = channel();
+
+        let thread_source = source.clone();
+        let thread_shared = shared.clone();
+
+        // Stops once the AssetServer is dropped
+        thread::spawn(move ||
+        {
+            for job in job_receiver
+            {
+                let result = catch_unwind(AssertUnwindSafe(|| run_job(thread_source.as_ref(), &thread_shared, &job)))
+                    .unwrap_or_else(|_| Err(RadError::Parse(format!("{}: Loader panicked", job.file))));
+
+                if completed_sender.send(Completed { file: job.file, root: job.root, reload: job.reload, result }).is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        Self
+        {
+            source,
+            shared,
+            loaders: vec![],
+            states: Mutex::new(HashMap::new()),
+            loading_files: Mutex::new(HashMap::new()),
+            failed_loads: Mutex::new(vec![]),
+            jobs,
+            completed: Mutex::new(completed),
+            watcher: None,
+            watched: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Lets the AssetServer hand out handles to `T`, reserving them through `reserver`
+    pub fn register<T : Asset>(&mut self, reserver : HandleReserver<T>)
+    {
+        self.shared.reservers.write().unwrap().insert(TypeId::of::<T>(), Box::new(reserver));
+    }
+
+    /// Loads files with the loader's extensions, taking over from loaders added before it for the same extensions
+    pub fn add_loader<L : AssetLoader>(&mut self, loader : L)
+    {
+        self.loaders.push(Arc::new(loader));
+    }
+
+    /// Reloads assets whenever their file changes, replacing them behind their existing handles.
+    /// Only applies to assets loaded after this is called, and only to sources backed by files on disk.
+    pub fn watch_for_changes(&mut self)
+    {
+        if self.watcher.is_none()
+        {
+            self.watcher = Some(FileWatcher::new(Duration::from_millis(250)));
+        }
+    }
+
+    pub fn is_watching_for_changes(&self) -> bool
+    {
+        self.watcher.is_some()
+    }
+
+    /// Keeps the processed form of every asset whose loader supports [`AssetLoader::process`] in `dir`,
+    /// loading from there instead of the source while the source file and loader settings haven't changed.
+    /// Processing happens on the loading thread the first time a file is loaded.
+    pub fn cache_processed_assets(&mut self, dir : impl Into<PathBuf>)
+    {
+        *self.shared.processed.write().unwrap() = Some(ProcessedCache::new(dir.into()));
+    }
+
+    pub fn is_caching_processed_assets(&self) -> bool
+    {
+        self.shared.processed.read().unwrap().is_some()
+    }
+
+    pub fn source(&self) -> &dyn AssetSource
+    {
+        self.source.as_ref()
+    }
+
+    /// The most recently added loader for the file's extension, that loads `asset_type` if given
+    fn loader_for(&self, file : &str, asset_type : Option<TypeId>) -> Option<Arc<dyn ErasedLoader>>
+    {
+        let extension = Path::new(file).extension()?.to_str()?;
+
+        self.loaders.iter()
+            .rev()
+            .find(|loader| loader.extensions().contains(&extension) && asset_type.map_or(true, |ty| loader.asset_type() == ty))
+            .cloned()
+    }
+
+    /// Starts loading `path` from the source. Loading a path that's still alive returns the same asset.
+    /// Sub-assets added by a loader are loaded with `path#label`.
+    pub fn load<T : Asset>(&self, path : impl AsRef<Path>) -> Handle<T>
+    {
+        let path = path.as_ref().to_string_lossy().into_owned();
+        let (handle, new) = self.shared.handle_for::<T>(&path);
+        if !new
+        {
+            return handle
+        }
+
+        let id = handle.downgrade().id();
+        let (file, label) = match path.split_once('#')
+        {
+            Some((file, label)) => (file, Some(label)),
+            None => (path.as_str(), None),
+        };
+
+        // Only the file's own asset has to match T, sub-assets can come out of a loader for any type
+        let Some(loader) = self.loader_for(file, label.is_none().then(TypeId::of::<T>)) else {
+            let error = Arc::new(RadError::NoLoader(format!("{} that loads {}", file, type_name::<T>())));
+            self.states.lock().unwrap().insert(id, LoadState::Failed(error.clone()));
+            self.failed_loads.lock().unwrap().push(AssetLoadFailed { path: file.to_string(), reload: false, error });
+            return handle
+        };
+
+        self.states.lock().unwrap().insert(id, LoadState::Loading);
+
+        {
+            let mut loading_files = self.loading_files.lock().unwrap();
+            let jobs = loading_files.entry(file.to_string()).or_insert(0);
+            if label.is_some() && *jobs > 0
+            {
+                // Every load of a file adds all of its sub-assets
+                return handle
+            }
+            *jobs += 1;
+        }
+
+        let root = label.is_none().then_some(id);
+        self.watch(file, root, &loader);
+
+        // Only fails if the loading thread died, the handle then just never loads
+        let _ = self.jobs.send(Job { file: file.to_string(), root, loader, reload: false });
+
+        handle
+    }
+
+    fn watch(&self, file : &str, root : Option<AssetId>, loader : &Arc<dyn ErasedLoader>)
+    {
+        let (Some(watcher), Some(local_path)) = (&self.watcher, self.source.local_path(file)) else {
+            return;
+        };
+
+        let mut watched = self.watched.lock().unwrap();
+        let entry = watched.entry(file.to_string()).or_insert_with(|| Watched { root: None, loader: loader.clone() });
+
+        if root.is_some()
+        {
+            *entry = Watched { root, loader: loader.clone() };
+        }
+
+        watcher.watch(local_path);
+    }
+
+    /// Whether the file's own asset or any of its sub-assets are still alive
+    fn file_in_use(&self, file : &str, root : Option<AssetId>) -> bool
+    {
+        let prefix = format!("{}#", file);
+
+        root.is_some_and(|root| self.shared.is_alive(root)) ||
+            self.shared.paths.lock().unwrap().iter()
+                .filter(|((_, path), _)| path.starts_with(&prefix))
+                .any(|(_, id)| self.shared.is_alive(*id))
+    }
+
+    /// Queues a reload for every file that changed and still has live assets
+    fn reload_changed(&self)
+    {
+        let Some(watcher) = &self.watcher else {
+            return;
+        };
+
+        let mut watched = self.watched.lock().unwrap();
+        for path in watcher.changed()
+        {
+            let Some((file, entry)) = watched.iter().find(|(file, _)| self.source.local_path(file).as_ref() == Some(&path)) else {
+                continue;
+            };
+            let file = file.clone();
+
+            if !self.file_in_use(&file, entry.root)
+            {
+                watched.remove(&file);
+                watcher.unwatch(&path);
+                continue;
+            }
+
+            let root = entry.root.filter(|root| self.shared.is_alive(*root));
+            let _ = self.jobs.send(Job { file, root, loader: entry.loader.clone(), reload: true });
+        }
+    }
+
+    pub fn load_state<T : 'static>(&self, handle : impl Into<WeakHandle<T>>) -> LoadState
+    {
+        self.states.lock().unwrap()
+            .get(&handle.into().id())
+            .cloned()
+            .unwrap_or(LoadState::NotLoaded)
+    }
+
+    pub fn is_loaded<T : 'static>(&self, handle : impl Into<WeakHandle<T>>) -> bool
+    {
+        self.load_state(handle) == LoadState::Loaded
+    }
+
+    /// Hands every load that has finished to the RenderAPI
+    pub(crate) fn upload_completed(&self, api : &mut dyn RenderAPI, world : &mut World)
+    {
+        self.reload_changed();
+
+        let completed : Vec<Completed> = self.completed.lock().unwrap().try_iter().collect();
+        let mut states = self.states.lock().unwrap();
+        let mut failures = std::mem::take(&mut *self.failed_loads.lock().unwrap());
+
+        for Completed { file, root, reload, result } in completed
+        {
+            let failed = match result
+            {
+                Ok(uploads) => {
+                    for PendingUpload { id, run } in uploads
+                    {
+                        match run(api, world)
+                        {
+                            Ok(()) => { states.insert(id, LoadState::Loaded); },
+                            Err(err) => {
+                                let error = Arc::new(err);
+                                // A failed reload leaves the previous asset in place
+                                if !(reload && states.get(&id) == Some(&LoadState::Loaded))
+                                {
+                                    states.insert(id, LoadState::Failed(error.clone()));
+                                }
+                                failures.push(AssetLoadFailed { path: file.clone(), reload, error });
+                            },
+                        }
+                    }
+                    None
+                },
+                Err(err) if reload => {
+                    failures.push(AssetLoadFailed { path: file.clone(), reload, error: Arc::new(err) });
+                    None
+                },
+                Err(err) => {
+                    let err = Arc::new(err);
+                    if let Some(root) = root
+                    {
+                        states.insert(root, LoadState::Failed(err.clone()));
+                    }
+                    failures.push(AssetLoadFailed { path: file.clone(), reload, error: err.clone() });
+                    Some(err)
+                },
+            };
+
+            if reload
+            {
+                continue;
+            }
+
+            {
+                let mut loading_files = self.loading_files.lock().unwrap();
+                if let Some(jobs) = loading_files.get_mut(&file)
+                {
+                    *jobs -= 1;
+                    if *jobs == 0
+                    {
+                        loading_files.remove(&file);
+                    }
+                }
+            }
+
+            // Sub-assets that were asked for but didn't come out of the file
+            let prefix = format!("{}#", file);
+            for ((_, path), id) in self.shared.paths.lock().unwrap().iter().filter(|((_, path), _)| path.starts_with(&prefix))
+            {
+                if let Some(state @ LoadState::Loading) = states.get_mut(id)
+                {
+                    *state = LoadState::Failed(failed.clone().unwrap_or_else(||
+                        Arc::new(RadError::Parse(format!("{} has no asset labeled {}", file, &path[prefix.len()..])))));
+                }
+            }
+        }
+        // Forget assets freed since the last frame, their handles can't be asked about any more
+        states.retain(|id, _| self.shared.is_alive(*id));
+        self.shared.forget_freed();
+
+        if let Some(mut events) = world.get_resource_mut::<Events<AssetLoadFailed>>()
+        {
+            events.send_batch(failures);
+        }
+
+        let write_failures = std::mem::take(&mut *self.shared.write_failures.lock().unwrap());
+        if let Some(mut events) = world.get_resource_mut::<Events<ProcessedAssetWriteFailed>>()
+        {
+            events.send_batch(write_failures);
+        }
+    }
+}
+
+pub(crate) fn upload_loaded_assets(world : &mut World)
+{
+    if !world.contains_resource::<AssetServer>()
+    {
+        return;
+    }
+
+    let Some(mut context) = world.remove_non_send_resource::<RenderContext>() else {
+        return;
+    };
+
+    world.resource_scope(|world, server : Mut<AssetServer>|
+    {
+        server.upload_completed(context.api.as_mut(), world);
+    });
+
+    world.insert_non_send_resource(context);
+}
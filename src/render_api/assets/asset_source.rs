@@ -0,0 +1,74 @@
+use std::{io, path::{Component, Path, PathBuf}};
+
+/// Where the [`AssetServer`](crate::AssetServer) reads files from.
+/// Paths are relative and always use `/` as the separator.
+pub trait AssetSource : Send + Sync + 'static
+{
+    fn read(&self, path : &str) -> io::Result<Vec<u8>>;
+
+    /// The file on disk behind `path`, if there is one, used to watch for changes
+    fn local_path(&self, _path : &str) -> Option<PathBuf>
+    {
+        None
+    }
+}
+
+/// Joins a source path onto `root`, refusing `..`, absolute paths and anything else that could reach outside it
+pub(crate) fn join_inside(root : &Path, path : &str) -> io::Result<PathBuf>
+{
+    match Path::new(path).components().find(|component| !matches!(component, Component::Normal(_)))
+    {
+        Some(_) => Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} isn't a relative path inside the asset folder", path))),
+        None => Ok(root.join(path)),
+    }
+}
+
+/// Reads loose files from a folder, never outside it
+pub struct FileSource
+{
+    root : PathBuf,
+}
+
+impl FileSource
+{
+    pub fn new(root : impl Into<PathBuf>) -> Self
+    {
+        Self { root: root.into() }
+    }
+}
+
+impl AssetSource for FileSource
+{
+    fn read(&self, path : &str) -> io::Result<Vec<u8>>
+    {
+        std::fs::read(join_inside(&self.root, path)?)
+    }
+
+    fn local_path(&self, path : &str) -> Option<PathBuf>
+    {
+        join_inside(&self.root, path).ok()
+    }
+}
+
+/// Stands in for a source that couldn't be opened, every read fails with the reason so each load reports it
+pub(crate) struct UnavailableSource
+{
+    kind : io::ErrorKind,
+    error : String,
+}
+
+impl UnavailableSource
+{
+    pub fn new(error : io::Error) -> Self
+    {
+        Self { kind: error.kind(), error: error.to_string() }
+    }
+}
+
+impl AssetSource for UnavailableSource
+{
+    fn read(&self, _path : &str) -> io::Result<Vec<u8>>
+    {
+        Err(io::Error::new(self.kind, self.error.clone()))
+    }
+}
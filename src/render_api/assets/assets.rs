@@ -1,179 +1,653 @@
-use std::{marker::PhantomData, sync::{mpsc::{channel, sync_channel, Receiver, Sender}, Arc}};
+use std::{any::{type_name, TypeId}, fmt::Debug, hash::{Hash, Hasher}, marker::PhantomData, sync::{mpsc::{channel, Receiver, Sender}, Arc, Mutex}};
 
-use bevy_ecs::system::Resource;
+use bevy_ecs::{event::{Event, Events}, system::Resource, world::World};
 
-// TODO: Assets<T> won't ever shrink, need to implement a way to clean up.
-// It shouldn't be a problem if the user doesn't create a ton of Assets
-// Although if you leave something running it might start a leak
-// It could use a garbage collector sorta thing, but I don't feel like that's the best way to fix it
-// I'll think about it.
+use crate::RadError;
 
-#[derive(Debug, Copy, Clone)]
-pub struct AssetHandle(u32, usize); // Magic, Inde
+// Strong handles tell the Assets they came from whenever they're cloned or dropped,
+// Assets::cleanup then applies those reference count changes and frees anything left unreferenced.
+// Weak handles don't take part in this, they can outlive the asset and be upgraded while it's alive.
 
-enum AssetEventType
+/// The reference counting part shared by typed and untyped handles
+#[derive(Debug)]
+struct StrongHandle
+{
+    generation : u32,
+    index : usize,
+    sender : Sender<HandleEvent>,
+}
+
+impl Clone for StrongHandle
+{
+    fn clone(&self) -> Self {
+        let _ = self.sender.send(HandleEvent { generation: self.generation, index: self.index, ty: HandleEventType::Clone });
+        Self { generation: self.generation, index: self.index, sender: self.sender.clone() }
+    }
+}
+
+impl Drop for StrongHandle
+{
+    fn drop(&mut self) {
+        // Fails if the Assets was dropped first, in which case there's nothing left to free
+        let _ = self.sender.send(HandleEvent { generation: self.generation, index: self.index, ty: HandleEventType::Drop });
+    }
+}
+
+/// Keeps the asset it points to alive, can only be used with an `Assets<T>`
+pub struct Handle<T>
+{
+    inner : StrongHandle,
+    _ty : PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T>
+{
+    pub fn downgrade(&self) -> WeakHandle<T>
+    {
+        WeakHandle { generation: self.inner.generation, index: self.inner.index, _ty: PhantomData }
+    }
+
+    /// Erases the asset type, e.g. to keep handles to different asset types in one collection
+    pub fn untyped(self) -> UntypedHandle
+        where T : 'static
+    {
+        UntypedHandle { inner: self.inner, type_id: TypeId::of::<T>() }
+    }
+}
+
+impl<T> Clone for Handle<T>
+{
+    fn clone(&self) -> Self {
+        Self { inner: self.inner.clone(), _ty: PhantomData }
+    }
+}
+
+impl<T> Debug for Handle<T>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Handle<{}>({}, {})", type_name::<T>(), self.inner.generation, self.inner.index)
+    }
+}
+
+impl<T> PartialEq for Handle<T>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.inner.generation == other.inner.generation && self.inner.index == other.inner.index
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T>
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.downgrade().hash(state)
+    }
+}
+
+/// Points to an asset without keeping it alive
+pub struct WeakHandle<T>
+{
+    generation : u32,
+    index : usize,
+    _ty : PhantomData<fn() -> T>,
+}
+
+impl<T : 'static> WeakHandle<T>
+{
+    pub(crate) fn id(&self) -> AssetId
+    {
+        AssetId { type_id: TypeId::of::<T>(), generation: self.generation, index: self.index }
+    }
+
+    /// Only gives a handle back if `id` is for an asset of type `T`
+    pub(crate) fn from_id(id : AssetId) -> Option<Self>
+    {
+        (id.type_id == TypeId::of::<T>()).then_some(Self { generation: id.generation, index: id.index, _ty: PhantomData })
+    }
+}
+
+impl<T> Clone for WeakHandle<T>
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for WeakHandle<T> {}
+
+impl<T> Debug for WeakHandle<T>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "WeakHandle<{}>({}, {})", type_name::<T>(), self.generation, self.index)
+    }
+}
+
+impl<T> PartialEq for WeakHandle<T>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.generation == other.generation && self.index == other.index
+    }
+}
+
+impl<T> Eq for WeakHandle<T> {}
+
+impl<T> Hash for WeakHandle<T>
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.generation.hash(state);
+        self.index.hash(state);
+    }
+}
+
+impl<T> From<&Handle<T>> for WeakHandle<T>
+{
+    fn from(handle : &Handle<T>) -> Self {
+        handle.downgrade()
+    }
+}
+
+impl<T> From<&WeakHandle<T>> for WeakHandle<T>
+{
+    fn from(handle : &WeakHandle<T>) -> Self {
+        *handle
+    }
+}
+
+/// Identifies an asset of any type, for keying maps of assets
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub(crate) struct AssetId
+{
+    type_id : TypeId,
+    generation : u32,
+    index : usize,
+}
+
+/// A strong handle to an asset of any type
+#[derive(Clone, Debug)]
+pub struct UntypedHandle
+{
+    inner : StrongHandle,
+    type_id : TypeId,
+}
+
+impl UntypedHandle
+{
+    pub fn type_id(&self) -> TypeId
+    {
+        self.type_id
+    }
+
+    pub fn is<T : 'static>(&self) -> bool
+    {
+        self.type_id == TypeId::of::<T>()
+    }
+
+    /// Gets the typed handle back, returning None if `T` isn't the type it was created with
+    pub fn typed<T : 'static>(self) -> Option<Handle<T>>
+    {
+        if self.is::<T>()
+        {
+            Some(Handle { inner: self.inner, _ty: PhantomData })
+        } else {
+            None
+        }
+    }
+}
+
+impl<T : 'static> From<Handle<T>> for UntypedHandle
+{
+    fn from(handle : Handle<T>) -> Self {
+        handle.untyped()
+    }
+}
+
+#[derive(Debug)]
+enum HandleEventType
 {
     Drop,
     Clone
 }
 
 // Thanks Bevy for the idea
-struct AssetEvent
+#[derive(Debug)]
+struct HandleEvent
 {
-    magic : u32,
+    generation : u32,
     index : usize,
-    ty :  AssetEventType
+    ty :  HandleEventType
+}
+
+/// Something that happened to an asset in an `Assets<T>`, sent to systems once per frame.
+/// Only sent for Assets with events enabled, which includes the RenderAPI's meshes and programs
+/// and anything added with [`App::init_asset`](crate::App::init_asset).
+pub enum AssetEvent<T>
+{
+    Added { handle : WeakHandle<T> },
+    /// Replaced through [`Assets::insert`], e.g. on hot reload, or changed in place and passed to [`Assets::mark_modified`]
+    Modified { handle : WeakHandle<T> },
+    Removed { handle : WeakHandle<T> },
+}
+
+impl<T> AssetEvent<T>
+{
+    pub fn handle(&self) -> WeakHandle<T>
+    {
+        match self
+        {
+            Self::Added { handle } | Self::Modified { handle } | Self::Removed { handle } => *handle,
+        }
+    }
+}
+
+impl<T : 'static> Event for AssetEvent<T> {}
+
+impl<T> Clone for AssetEvent<T>
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for AssetEvent<T> {}
+
+impl<T> PartialEq for AssetEvent<T>
+{
+    fn eq(&self, other: &Self) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other) && self.handle() == other.handle()
+    }
+}
+
+impl<T> Eq for AssetEvent<T> {}
+
+impl<T> Debug for AssetEvent<T>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self
+        {
+            Self::Added { .. } => "Added",
+            Self::Modified { .. } => "Modified",
+            Self::Removed { .. } => "Removed",
+        };
+        f.debug_struct(name).field("handle", &self.handle()).finish()
+    }
 }
 
+/// Sends `events` to systems if `AssetEvent<T>` has been registered with `App::add_event`
+pub(crate) fn send_asset_events<T : 'static>(world : &mut World, events : impl IntoIterator<Item = AssetEvent<T>>)
+{
+    if let Some(mut sender) = world.get_resource_mut::<Events<AssetEvent<T>>>()
+    {
+        sender.extend(events);
+    }
+}
+
+/// Generation and reference count of a slot, shared between an Assets and its HandleReservers
+#[derive(Default)]
+struct SlotInfo
+{
+    generation : u32,
+    references : u32,
+}
+
+/// Hands out slots from a free list, each slot has its own generation which is bumped whenever
+/// it's freed so handles to the old asset can't reach whatever is put there next.
+#[derive(Default)]
+struct Allocator
+{
+    slots : Vec<SlotInfo>,
+    free : Vec<usize>,
+}
+
+impl Allocator
+{
+    fn allocate(&mut self) -> (u32, usize)
+    {
+        let index = match self.free.pop()
+        {
+            Some(index) => index,
+            None => {
+                self.slots.push(SlotInfo::default());
+                self.slots.len() - 1
+            }
+        };
+
+        let slot = &mut self.slots[index];
+        slot.references = 1;
+        (slot.generation, index)
+    }
+
+    fn release(&mut self, index : usize)
+    {
+        let slot = &mut self.slots[index];
+        slot.references = 0;
+
+        // A slot that has used up its generations is retired rather than risk a stale handle matching again
+        if let Some(generation) = slot.generation.checked_add(1)
+        {
+            slot.generation = generation;
+            self.free.push(index);
+        }
+    }
+
+    fn get_mut(&mut self, generation : u32, index : usize) -> Option<&mut SlotInfo>
+    {
+        self.slots.get_mut(index).filter(|slot| slot.generation == generation && slot.references > 0)
+    }
+}
+
+/// Reserves handles into an `Assets<T>` without needing access to it, the asset is inserted later.
+/// Used by the AssetServer to hand out handles before a file has finished loading.
+pub struct HandleReserver<T>
+{
+    allocator : Arc<Mutex<Allocator>>,
+    sender : Sender<HandleEvent>,
+    _ty : PhantomData<fn() -> T>,
+}
+
+impl<T> HandleReserver<T>
+{
+    pub fn reserve(&self) -> Handle<T>
+    {
+        let (generation, index) = self.allocator.lock().unwrap().allocate();
+        Handle { inner: StrongHandle { generation, index, sender: self.sender.clone() }, _ty: PhantomData }
+    }
+
+    /// Gets a new strong handle if the slot is still alive, whether or not the asset has been inserted yet
+    pub fn upgrade(&self, handle : impl Into<WeakHandle<T>>) -> Option<Handle<T>>
+    {
+        let WeakHandle { generation, index, .. } = handle.into();
+
+        self.allocator.lock().unwrap().get_mut(generation, index)?.references += 1;
+        Some(Handle { inner: StrongHandle { generation, index, sender: self.sender.clone() }, _ty: PhantomData })
+    }
+
+    /// Whether the slot is still alive, it may not have had an asset put in it yet
+    pub fn is_alive(&self, handle : impl Into<WeakHandle<T>>) -> bool
+    {
+        let WeakHandle { generation, index, .. } = handle.into();
+        self.allocator.lock().unwrap().get_mut(generation, index).is_some()
+    }
+}
+
+impl<T> Clone for HandleReserver<T>
+{
+    fn clone(&self) -> Self {
+        Self { allocator: self.allocator.clone(), sender: self.sender.clone(), _ty: PhantomData }
+    }
+}
+
+/// Storage for one type of asset.
+/// Slots come from a free list and are freed once every strong handle to them has been dropped.
+/// Handles are typed by `T` while the values stored are `V`, which lets a RenderAPI keep its own form
+/// of an asset behind the same handles as every other RenderAPI, e.g. the headless API's `MeshBuilder`s behind `Handle<OGLMesh>`.
 #[derive(Resource)]
-pub struct Assets<T>
+pub struct Assets<T, V = T>
 {
-    assets : Vec<Option<(u32, T, u32)>>,
-    magic : u32,
+    values : Vec<Option<(u32, V)>>, // Generation, Asset
+    len : usize,
+    /// None until events are enabled
+    events : Option<Vec<AssetEvent<T>>>,
+    allocator : Arc<Mutex<Allocator>>,
+    sender : Sender<HandleEvent>,
+    receiver : Mutex<Receiver<HandleEvent>>,
 }
 
 impl<T> Assets<T>
 {
     pub fn new() -> Self
     {
+        Self::default()
+    }
+}
+
+impl<T, V> Default for Assets<T, V>
+{
+    fn default() -> Self
+    {
+        let (sender, receiver) = channel();
         Self
         {
-            assets: vec![],
-            magic : 0,
+            values: vec![],
+            len: 0,
+            events: None,
+            allocator: Arc::default(),
+            sender,
+            receiver : Mutex::new(receiver),
         }
     }
+}
 
-    pub fn add_asset(&mut self, v : T) -> AssetHandle
+impl<T, V> Assets<T, V>
+{
+    pub fn add_asset(&mut self, v : V) -> Handle<T>
     {
-        self.magic = self.magic.overflowing_add(1).0;
-        let index = {
-            self.assets.iter()
-                .enumerate()
-                .find_map(|v| if v.1.is_none() {Some(v.0)} else {None})
-                .unwrap_or_else(||
-                {
-                    self.assets.push(None);
-                    self.assets.len() - 1
-                })
-        };
-        self.assets[index] = Some((self.magic, v, 1));
-        AssetHandle(self.magic, index)
+        let handle = self.reserver().reserve();
+        self.set_value(handle.inner.generation, handle.inner.index, v);
+        handle
+    }
+
+    /// Starts recording [`AssetEvent`]s, to be taken with [`Assets::drain_events`]
+    pub fn enable_events(&mut self)
+    {
+        self.events.get_or_insert_with(Vec::new);
+    }
+
+    /// Every event since the last call, in the order they happened
+    pub fn drain_events(&mut self) -> std::vec::Drain<'_, AssetEvent<T>>
+    {
+        self.events.get_or_insert_with(Vec::new).drain(..)
+    }
+
+    fn push_event(&mut self, event : AssetEvent<T>)
+    {
+        if let Some(events) = &mut self.events
+        {
+            events.push(event);
+        }
     }
 
-    pub fn remove_asset(&mut self, handle : &AssetHandle) -> Result<(), ()>
+    pub fn reserver(&self) -> HandleReserver<T>
     {
-        let asset = self.get_asset_raw_mut(handle);
+        HandleReserver { allocator: self.allocator.clone(), sender: self.sender.clone(), _ty: PhantomData }
+    }
 
-        if asset.is_none()
+    /// Puts an asset behind a reserved handle, replacing the asset already there.
+    /// Gives the asset back if the handle's slot has been freed.
+    pub fn insert(&mut self, handle : impl Into<WeakHandle<T>>, v : V) -> Result<Option<V>, V>
+    {
+        let WeakHandle { generation, index, .. } = handle.into();
+
+        if self.allocator.lock().unwrap().get_mut(generation, index).is_none()
         {
-            return Err(())
+            return Err(v)
         }
 
-        *asset.unwrap() = None;
+        Ok(self.set_value(generation, index, v))
+    }
+
+    fn set_value(&mut self, generation : u32, index : usize, v : V) -> Option<V>
+    {
+        if self.values.len() <= index
+        {
+            self.values.resize_with(index + 1, || None);
+        }
 
-        Ok(())
+        let old = self.values[index].replace((generation, v)).map(|(_, old)| old);
+        let handle = WeakHandle { generation, index, _ty: PhantomData };
+        if old.is_none()
+        {
+            self.len += 1;
+            self.push_event(AssetEvent::Added { handle });
+        } else {
+            self.push_event(AssetEvent::Modified { handle });
+        }
+        old
     }
 
-    fn remove_asset_unchecked(&mut self, index : usize) -> Result<(), ()>
+    /// Removes the asset even if strong handles to it still exist
+    pub fn remove_asset(&mut self, handle : impl Into<WeakHandle<T>>) -> Result<V, RadError>
     {
-        let asset = &mut self.assets[index];
+        let WeakHandle { generation, index, .. } = handle.into();
 
-        *asset = None;
+        if self.get_asset(WeakHandle { generation, index, _ty: PhantomData }).is_none()
+        {
+            return Err(RadError::InvalidHandle)
+        }
 
-        Ok(())
+        self.allocator.lock().unwrap().release(index);
+        Ok(self.take_value(index).unwrap())
     }
 
+    fn take_value(&mut self, index : usize) -> Option<V>
+    {
+        let (generation, asset) = self.values.get_mut(index)?.take()?;
+        self.len -= 1;
+        self.push_event(AssetEvent::Removed { handle: WeakHandle { generation, index, _ty: PhantomData } });
+        Some(asset)
+    }
 
-    /// Return type is wild
-    fn get_asset_raw_mut(&mut self, handle : &AssetHandle) -> Option<&mut Option<(u32, T, u32)>>
+    /// Gets a new strong handle if the asset is still alive
+    pub fn upgrade(&mut self, handle : impl Into<WeakHandle<T>>) -> Option<Handle<T>>
     {
-        let magic = handle.0;
-        let index = handle.1;
+        let handle = handle.into();
 
-        if let Some(asset_raw) = self.assets.get_mut(index)
+        if !self.contains(handle)
         {
-            if let Some((asset_magic, asset, references)) = asset_raw
-            {
-                if magic == *asset_magic
-                {
-                    return Some(asset_raw)
-                }
-            }
+            return None
         }
 
-        None
+        self.reserver().upgrade(handle)
+    }
+
+    pub fn contains(&self, handle : impl Into<WeakHandle<T>>) -> bool
+    {
+        self.get_asset(handle).is_some()
+    }
+
+    /// How many assets are currently stored
+    pub fn len(&self) -> usize
+    {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.len == 0
     }
 
-    pub fn get_asset(&self, handle : &AssetHandle) -> Option<&T>
+    pub fn iter(&self) -> impl Iterator<Item = (WeakHandle<T>, &V)>
     {
-        let magic = handle.0;
-        let index = handle.1;
+        self.values.iter()
+            .enumerate()
+            .filter_map(|(index, value)|
+            {
+                let (generation, asset) = value.as_ref()?;
+                Some((WeakHandle { generation: *generation, index, _ty: PhantomData }, asset))
+            })
+    }
 
-        if let Some(asset) = self.assets.get(index)
+    /// Doesn't send [`AssetEvent::Modified`], see [`Assets::mark_modified`]
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (WeakHandle<T>, &mut V)>
+    {
+        self.values.iter_mut()
+            .enumerate()
+            .filter_map(|(index, value)|
+            {
+                let (generation, asset) = value.as_mut()?;
+                Some((WeakHandle { generation: *generation, index, _ty: PhantomData }, asset))
+            })
+    }
+
+    /// Removes every asset, even ones with strong handles still around
+    pub fn drain(&mut self) -> std::vec::IntoIter<(WeakHandle<T>, V)>
+    {
+        let mut drained = Vec::with_capacity(self.len);
+
+        for index in 0..self.values.len()
         {
-            if let Some((asset_magic, asset, _)) = asset
+            if let Some((generation, _)) = self.values[index]
             {
-                if magic == *asset_magic
-                {
-                    return Some(asset)
-                }
+                self.allocator.lock().unwrap().release(index);
+                drained.push((WeakHandle { generation, index, _ty: PhantomData }, self.take_value(index).unwrap()));
             }
         }
 
-        None
+        drained.into_iter()
     }
 
-    pub fn get_asset_mut(&mut self, handle : &AssetHandle) -> Option<&mut T>
+    /// Applies reference count changes from strong handles, freeing assets that are no longer referenced.
+    /// Returns how many assets were freed.
+    pub fn cleanup(&mut self) -> usize
     {
-        let magic = handle.0;
-        let index = handle.1;
+        let messages : Vec<HandleEvent> = self.receiver.lock().unwrap().try_iter().collect();
+        let mut freed = 0;
 
-        if let Some(asset) = self.assets.get_mut(index)
+        for message in messages
         {
-            if let Some((asset_magic, asset, _)) = asset
+            let mut allocator = self.allocator.lock().unwrap();
+            let Some(slot) = allocator.get_mut(message.generation, message.index) else {
+                // Already removed
+                continue;
+            };
+
+            match message.ty
+            {
+                HandleEventType::Clone => slot.references += 1,
+                HandleEventType::Drop => slot.references -= 1,
+            }
+
+            if slot.references == 0
             {
-                if magic == *asset_magic
+                allocator.release(message.index);
+                drop(allocator);
+
+                // Reserved slots may never have had an asset put in them
+                if self.take_value(message.index).is_some()
                 {
-                    return Some(asset)
+                    freed += 1;
                 }
             }
         }
-        
-        None
-    }
-
-    // pub fn cleanup(&mut self)
-    // {
-    //     while let Ok(message) = self.reciever.try_recv()
-    //     {
-    //         let ty = message.ty;
-    //         let magic = message.magic;
-    //         let index = message.index;
-
-    //         match ty
-    //         {
-    //             AssetEventType::Drop => 
-    //             {
-    //                 let asset : &Option<_> = &self.assets[index];
-    //                 if asset.is_none()
-    //                 {
-    //                     continue;
-    //                 }
-    //                 self.assets[index].take();
-    //             },
-    //             AssetEventType::Clone => 
-    //             {
-    //                 let asset = &mut self.assets[index];
-    //                 if asset.is_none()
-    //                 {
-    //                     continue;
-    //                 }
-    //                 if let Some(asset) = asset
-    //                 {
-    //                     asset.2 += 1;
-    //                 }
-    //             },
-    //         }
-    //     }
-    // }
-}
\ No newline at end of file
+
+        freed
+    }
+
+    pub fn get_asset(&self, handle : impl Into<WeakHandle<T>>) -> Option<&V>
+    {
+        let WeakHandle { generation, index, .. } = handle.into();
+
+        match self.values.get(index)
+        {
+            Some(Some((asset_generation, asset))) if *asset_generation == generation => Some(asset),
+            _ => None,
+        }
+    }
+
+    /// Doesn't send [`AssetEvent::Modified`], call [`Assets::mark_modified`] once the asset has actually changed
+    pub fn get_asset_mut(&mut self, handle : impl Into<WeakHandle<T>>) -> Option<&mut V>
+    {
+        let WeakHandle { generation, index, .. } = handle.into();
+
+        match self.values.get_mut(index)
+        {
+            Some(Some((asset_generation, asset))) if *asset_generation == generation => Some(asset),
+            _ => None,
+        }
+    }
+
+    /// Sends [`AssetEvent::Modified`] for an asset changed in place through [`Assets::get_asset_mut`] or [`Assets::iter_mut`].
+    /// Returns false if the asset doesn't exist.
+    pub fn mark_modified(&mut self, handle : impl Into<WeakHandle<T>>) -> bool
+    {
+        let handle = handle.into();
+
+        if !self.contains(handle)
+        {
+            return false
+        }
+
+        self.push_event(AssetEvent::Modified { handle });
+        true
+    }
+}
@@ -0,0 +1,70 @@
+use std::{collections::HashMap, path::{Path, PathBuf}, sync::{mpsc::{channel, Receiver}, Arc, Mutex}, thread, time::{Duration, SystemTime}};
+
+fn modified(path : &Path) -> Option<SystemTime>
+{
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Polls the modification time of watched files on its own thread
+pub(crate) struct FileWatcher
+{
+    files : Arc<Mutex<HashMap<PathBuf, Option<SystemTime>>>>,
+    changes : Mutex<Receiver<PathBuf>>,
+}
+
+impl FileWatcher
+{
+    pub fn new(interval : Duration) -> Self
+    {
+        let files : Arc<Mutex<HashMap<PathBuf, Option<SystemTime>>>> = Arc::default();
+        let (sender, changes) = channel();
+
+        let weak_files = Arc::downgrade(&files);
+        thread::spawn(move ||
+        {
+            // Stops once the watcher is dropped
+            while let Some(files) = weak_files.upgrade()
+            {
+                for (path, last_modified) in files.lock().unwrap().iter_mut()
+                {
+                    let now = modified(path);
+
+                    // Files that are missing mid-save are picked up again once they're written
+                    if now.is_some() && now != *last_modified
+                    {
+                        *last_modified = now;
+                        if sender.send(path.clone()).is_err()
+                        {
+                            return;
+                        }
+                    }
+                }
+
+                drop(files);
+                thread::sleep(interval);
+            }
+        });
+
+        Self { files, changes: Mutex::new(changes) }
+    }
+
+    pub fn watch(&self, path : PathBuf)
+    {
+        let last_modified = modified(&path);
+        self.files.lock().unwrap().entry(path).or_insert(last_modified);
+    }
+
+    pub fn unwatch(&self, path : &Path)
+    {
+        self.files.lock().unwrap().remove(path);
+    }
+
+    /// Every file that changed since the last call
+    pub fn changed(&self) -> Vec<PathBuf>
+    {
+        let mut changed : Vec<PathBuf> = self.changes.lock().unwrap().try_iter().collect();
+        changed.sort();
+        changed.dedup();
+        changed
+    }
+}
@@ -1 +1,6 @@
-import!(assets);
\ No newline at end of file
+import!(assets);
+import!(asset_server);
+import!(file_watcher);
+import!(asset_source);
+import!(archive);
+import!(processed);
\ No newline at end of file
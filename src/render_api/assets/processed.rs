@@ -0,0 +1,77 @@
+use std::{io::{self, Read}, path::PathBuf};
+
+use crate::{content_hash, join_inside, read_u32, read_u64};
+
+// Layout, all integers little endian:
+//   magic "RADPROC\0", version u32, key u64
+//   whatever the loader's AssetLoader::process returned
+
+const MAGIC : &[u8; 8] = b"RADPROC\0";
+const VERSION : u32 = 1;
+
+/// Processed assets stored in a folder, one file per source file next to where it sits in the source.
+/// A file only counts as up to date while its key matches the source it came from.
+#[derive(Clone)]
+pub(crate) struct ProcessedCache
+{
+    dir : PathBuf,
+}
+
+impl ProcessedCache
+{
+    pub fn new(dir : PathBuf) -> Self
+    {
+        Self { dir }
+    }
+
+    /// Changes whenever the source file, the loader's processed format or its settings do
+    pub fn key(source : &[u8], format : &str, settings : u64) -> u64
+    {
+        let mut key = content_hash(source).to_le_bytes().to_vec();
+        key.extend_from_slice(&settings.to_le_bytes());
+        key.extend_from_slice(format.as_bytes());
+        content_hash(&key)
+    }
+
+    /// Fails for files that would land outside the cache folder
+    fn path(&self, file : &str) -> io::Result<PathBuf>
+    {
+        join_inside(&self.dir, &format!("{}.processed", file))
+    }
+
+    /// The processed form of `file`, None if there isn't one or it's out of date
+    pub fn read(&self, file : &str, key : u64) -> Option<Vec<u8>>
+    {
+        let bytes = std::fs::read(self.path(file).ok()?).ok()?;
+        let mut reader = bytes.as_slice();
+
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic).ok()?;
+        if &magic != MAGIC || read_u32(&mut reader).ok()? != VERSION || read_u64(&mut reader).ok()? != key
+        {
+            return None
+        }
+
+        Some(reader.to_vec())
+    }
+
+    pub fn write(&self, file : &str, key : u64, processed : &[u8]) -> io::Result<()>
+    {
+        let path = self.path(file)?;
+        if let Some(parent) = path.parent()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 4 + 8 + processed.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&key.to_le_bytes());
+        bytes.extend_from_slice(processed);
+
+        // Written aside and moved into place so a running game never reads half a file
+        let partial = path.with_extension("partial");
+        std::fs::write(&partial, bytes)?;
+        std::fs::rename(partial, path)
+    }
+}
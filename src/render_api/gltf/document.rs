@@ -0,0 +1,806 @@
+use std::{io, path::Path};
+
+use base64::Engine;
+use percent_encoding::percent_decode_str;
+use serde_json::Value;
+
+use crate::{read_u32, Colour, DefaultMaterial, Indices, Mesh, MeshBuilder, RadError, Vertex};
+
+// GLB layout, all integers little endian:
+//   magic "glTF", version u32, total length u32
+//   chunks of length u32, type u32, data padded to 4 bytes. The JSON chunk comes first, then an optional BIN chunk
+
+const GLB_MAGIC : u32 = 0x46546C67;
+const JSON_CHUNK : u32 = 0x4E4F534A;
+const BIN_CHUNK : u32 = 0x004E4942;
+
+/// Extensions that can be listed in `extensionsRequired` without refusing the file
+const SUPPORTED_EXTENSIONS : &[&str] = &["KHR_materials_unlit"];
+
+/// Accessors without a buffer view are all zeros, so nothing in the file limits how big they can claim to be
+const MAX_ZEROED_ELEMENTS : usize = 1 << 24;
+
+pub(super) const IDENTITY : [[f32; 4]; 4] = [[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0]];
+
+fn parse_error(message : String) -> RadError
+{
+    RadError::Parse(format!("glTF - {}", message))
+}
+
+/// `a * b` for column major matrices
+pub(super) fn multiply(a : [[f32; 4]; 4], b : [[f32; 4]; 4]) -> [[f32; 4]; 4]
+{
+    [0, 1, 2, 3].map(|column| [0, 1, 2, 3].map(|row| (0..4).map(|k| a[k][row] * b[column][k]).sum()))
+}
+
+/// Metallic-roughness parameters of a glTF material, in linear colour. Textures aren't read.
+#[derive(Clone, PartialEq, Debug)]
+pub struct GltfMaterial
+{
+    pub name : Option<String>,
+    /// RGBA, alpha is kept but DefaultMaterial draws everything opaque
+    pub base_colour : [f32; 4],
+    pub metallic : f32,
+    pub roughness : f32,
+    pub emissive : [f32; 3],
+    pub double_sided : bool,
+    /// Set by `KHR_materials_unlit`
+    pub unlit : bool,
+}
+
+/// The material glTF uses for primitives without one
+impl Default for GltfMaterial
+{
+    fn default() -> Self
+    {
+        Self { name: None, base_colour: [1.0; 4], metallic: 1.0, roughness: 1.0, emissive: [0.0; 3], double_sided: false, unlit: false }
+    }
+}
+
+impl From<&GltfMaterial> for DefaultMaterial
+{
+    fn from(material : &GltfMaterial) -> Self
+    {
+        let [r, g, b, _] = material.base_colour;
+        Self { shading_enabled: !material.unlit, base_colour: Colour(r, g, b), ..Default::default() }
+    }
+}
+
+/// One draw of a glTF mesh, with its own material
+#[derive(Clone)]
+pub struct GltfPrimitive<M>
+{
+    pub mesh : M,
+    /// Index into [`Gltf::materials`], None for the default material
+    pub material : Option<usize>,
+}
+
+#[derive(Clone)]
+pub struct GltfMesh<M>
+{
+    pub name : Option<String>,
+    pub primitives : Vec<GltfPrimitive<M>>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct GltfNode
+{
+    pub name : Option<String>,
+    /// Relative to the parent node, column major like [`Transform::as_uniform`](crate::Transform::as_uniform)
+    pub matrix : [[f32; 4]; 4],
+    /// Index into [`Gltf::meshes`]
+    pub mesh : Option<usize>,
+    /// Indices into [`Gltf::nodes`]
+    pub children : Vec<usize>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct GltfScene
+{
+    pub name : Option<String>,
+    /// The root nodes, indices into [`Gltf::nodes`]
+    pub nodes : Vec<usize>,
+}
+
+/// The contents of a glTF 2.0 file.
+/// Meshes are `MeshBuilder`s straight after parsing and [`Mesh`]es once they've been handed to the RenderAPI,
+/// either by the [`GltfLoader`](crate::GltfLoader) or [`App::register_gltf`](crate::App::register_gltf).
+///
+/// Positions and normals are used as they are, like OBJ files. Texture coordinates are flipped to put v = 0 at the bottom, also like OBJ files.
+#[derive(Clone)]
+pub struct Gltf<M = Mesh>
+{
+    pub meshes : Vec<GltfMesh<M>>,
+    pub materials : Vec<GltfMaterial>,
+    /// Every node is a child of at most one other node, and there are no cycles
+    pub nodes : Vec<GltfNode>,
+    pub scenes : Vec<GltfScene>,
+    /// The scene to show when none is picked
+    pub default_scene : Option<usize>,
+}
+
+impl<M> Gltf<M>
+{
+    /// `index`, or the default scene if None, or the first scene if there isn't a default
+    pub fn scene(&self, index : Option<usize>) -> Option<&GltfScene>
+    {
+        self.scenes.get(index.or(self.default_scene).unwrap_or(0))
+    }
+
+    /// Replaces every primitive's mesh, `map` is given the mesh and primitive index
+    pub fn map_meshes<N>(self, mut map : impl FnMut(usize, usize, M) -> Result<N, RadError>) -> Result<Gltf<N>, RadError>
+    {
+        let meshes = self.meshes.into_iter().enumerate().map(|(mesh_index, mesh)|
+        {
+            let primitives = mesh.primitives.into_iter().enumerate()
+                .map(|(primitive_index, primitive)| Ok(GltfPrimitive { mesh: map(mesh_index, primitive_index, primitive.mesh)?, material: primitive.material }))
+                .collect::<Result<_, RadError>>()?;
+
+            Ok(GltfMesh { name: mesh.name, primitives })
+        }).collect::<Result<_, RadError>>()?;
+
+        Ok(Gltf { meshes, materials: self.materials, nodes: self.nodes, scenes: self.scenes, default_scene: self.default_scene })
+    }
+}
+
+impl Gltf<MeshBuilder>
+{
+    /// Reads a `.gltf` or `.glb` file, along with any buffers in separate files next to it
+    pub fn open(path : impl AsRef<Path>) -> Result<Self, RadError>
+    {
+        let path = path.as_ref();
+        let folder = path.parent().unwrap_or(Path::new(""));
+
+        Self::from_slice(&std::fs::read(path)?, |uri|
+        {
+            let path = folder.join(uri);
+            std::fs::read(&path).map_err(|err| RadError::Io(io::Error::new(err.kind(), format!("{}: {}", path.display(), err))))
+        })
+    }
+
+    /// Parses a `.gltf` or `.glb` file. Buffers that aren't embedded in the file are read with `read`,
+    /// which is given their path relative to the file.
+    /// Files with nodes that a [`Transform`](crate::Transform) can't place, see [`Gltf::spawn_scene`], are refused.
+    pub fn from_slice(bytes : &[u8], mut read : impl FnMut(&str) -> Result<Vec<u8>, RadError>) -> Result<Self, RadError>
+    {
+        let (json, binary) = split_glb(bytes)?;
+        let json : Value = serde_json::from_str(json).map_err(|err| parse_error(format!("Invalid JSON - {}", err)))?;
+
+        let version = json.get("asset").and_then(|asset| asset.get("version")).and_then(Value::as_str);
+        if !version.is_some_and(|version| version.starts_with("2."))
+        {
+            return Err(parse_error(format!("Only version 2 is supported, the file is version {}", version.unwrap_or("unknown"))))
+        }
+
+        for extension in array(&json, "extensionsRequired")?
+        {
+            let extension = extension.as_str().unwrap_or_default();
+            if !SUPPORTED_EXTENSIONS.contains(&extension)
+            {
+                return Err(parse_error(format!("The file requires extension {}, which isn't supported", extension)))
+            }
+        }
+
+        let buffers = array(&json, "buffers")?.iter().enumerate()
+            .map(|(index, buffer)| load_buffer(index, buffer, binary, &mut read))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let views = array(&json, "bufferViews")?.iter()
+            .map(|view| BufferView::parse(view, &buffers))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let document = Document { json: &json, buffers, views };
+
+        let materials = array(&json, "materials")?.iter().map(parse_material).collect::<Result<Vec<_>, _>>()?;
+        let meshes = array(&json, "meshes")?.iter().map(|mesh| document.mesh(mesh, materials.len())).collect::<Result<Vec<_>, _>>()?;
+        let nodes = array(&json, "nodes")?.iter().map(|node| parse_node(node, meshes.len())).collect::<Result<Vec<_>, _>>()?;
+        let parents = check_hierarchy(&nodes)?;
+        check_transforms(&nodes, &parents)?;
+
+        let scenes = array(&json, "scenes")?.iter().map(|scene|
+        {
+            Ok(GltfScene { name: name(scene), nodes: indices(scene, "nodes", nodes.len())? })
+        }).collect::<Result<Vec<_>, RadError>>()?;
+
+        let default_scene = index(&json, "scene", scenes.len())?;
+
+        Ok(Gltf { meshes, materials, nodes, scenes, default_scene })
+    }
+}
+
+fn json_text(bytes : &[u8]) -> Result<&str, RadError>
+{
+    let text = std::str::from_utf8(bytes).map_err(|_| parse_error(String::from("JSON isn't valid UTF-8")))?;
+    Ok(text.strip_prefix('\u{feff}').unwrap_or(text))
+}
+
+fn glb_ends_early<T>(_ : T) -> RadError
+{
+    parse_error(String::from("GLB file ends early"))
+}
+
+/// The type and data of the next GLB chunk, None once there are no more
+fn glb_chunk<'a>(reader : &mut &'a [u8]) -> Result<Option<(u32, &'a [u8])>, RadError>
+{
+    if reader.is_empty()
+    {
+        return Ok(None)
+    }
+
+    let length = read_u32(reader).map_err(glb_ends_early)? as usize;
+    let kind = read_u32(reader).map_err(glb_ends_early)?;
+    let data = reader.get(..length).ok_or_else(|| glb_ends_early(()))?;
+    *reader = &reader[length..];
+
+    Ok(Some((kind, data)))
+}
+
+/// The JSON text and BIN chunk of a GLB file, or the whole file as JSON if it isn't one
+fn split_glb(bytes : &[u8]) -> Result<(&str, Option<&[u8]>), RadError>
+{
+    let mut reader = bytes;
+    if read_u32(&mut reader).ok() != Some(GLB_MAGIC)
+    {
+        return Ok((json_text(bytes)?, None))
+    }
+
+    let version = read_u32(&mut reader).map_err(glb_ends_early)?;
+    if version != 2
+    {
+        return Err(parse_error(format!("Only GLB version 2 is supported, the file is version {}", version)))
+    }
+
+    let length = read_u32(&mut reader).map_err(glb_ends_early)? as usize;
+    let mut reader = bytes.get(12..length).ok_or_else(|| glb_ends_early(()))?;
+
+    let json = match glb_chunk(&mut reader)?
+    {
+        Some((JSON_CHUNK, json)) => json_text(json)?,
+        _ => return Err(parse_error(String::from("GLB file doesn't start with a JSON chunk"))),
+    };
+
+    // Chunks of unknown types are skipped, as the spec asks
+    let mut binary = None;
+    while let Some((kind, data)) = glb_chunk(&mut reader)?
+    {
+        if kind == BIN_CHUNK && binary.is_none()
+        {
+            binary = Some(data);
+        }
+    }
+
+    Ok((json, binary))
+}
+
+fn load_buffer(index : usize, buffer : &Value, binary : Option<&[u8]>, read : &mut impl FnMut(&str) -> Result<Vec<u8>, RadError>) -> Result<Vec<u8>, RadError>
+{
+    let length = buffer.get("byteLength").and_then(as_usize)
+        .ok_or_else(|| parse_error(format!("Buffer {} has no byteLength", index)))?;
+
+    let mut bytes = match buffer.get("uri").and_then(Value::as_str)
+    {
+        Some(uri) if uri.starts_with("data:") => {
+            let (_, data) = uri.split_once(";base64,")
+                .ok_or_else(|| parse_error(format!("Buffer {} is a data URI that isn't base64", index)))?;
+            base64::engine::general_purpose::STANDARD.decode(data)
+                .map_err(|err| parse_error(format!("Buffer {} has invalid base64 - {}", index, err)))?
+        },
+        // Percent encoded, e.g. %20 for spaces in file names
+        Some(uri) => read(&percent_decode_str(uri).decode_utf8_lossy())?,
+        // Only the first buffer of a GLB file can be its BIN chunk
+        None if index == 0 => binary.ok_or_else(|| parse_error(String::from("Buffer 0 has no uri and there's no BIN chunk")))?.to_vec(),
+        None => return Err(parse_error(format!("Buffer {} has no uri", index))),
+    };
+
+    // BIN chunks are padded to 4 bytes, which the byteLength doesn't include
+    if bytes.len() < length
+    {
+        return Err(parse_error(format!("Buffer {} is {} bytes but should be {}", index, bytes.len(), length)))
+    }
+    bytes.truncate(length);
+
+    Ok(bytes)
+}
+
+/// Only whole, non-negative numbers count
+fn as_usize(value : &Value) -> Option<usize>
+{
+    value.as_u64().and_then(|value| usize::try_from(value).ok())
+}
+
+/// An empty slice if `key` is missing
+fn array<'a>(json : &'a Value, key : &str) -> Result<&'a [Value], RadError>
+{
+    match json.get(key)
+    {
+        Some(value) => value.as_array().map(Vec::as_slice).ok_or_else(|| parse_error(format!("{} isn't an array", key))),
+        None => Ok(&[]),
+    }
+}
+
+fn name(json : &Value) -> Option<String>
+{
+    json.get("name").and_then(Value::as_str).map(String::from)
+}
+
+/// An optional index under `key` that has to be below `count`
+fn index(json : &Value, key : &str, count : usize) -> Result<Option<usize>, RadError>
+{
+    match json.get(key)
+    {
+        Some(value) => match as_usize(value)
+        {
+            Some(index) if index < count => Ok(Some(index)),
+            _ => Err(parse_error(format!("{} {} is out of range for {} items", key, value, count))),
+        },
+        None => Ok(None),
+    }
+}
+
+fn indices(json : &Value, key : &str, count : usize) -> Result<Vec<usize>, RadError>
+{
+    array(json, key)?.iter().map(|value| match as_usize(value)
+    {
+        Some(index) if index < count => Ok(index),
+        _ => Err(parse_error(format!("{} {} is out of range for {} items", key, value, count))),
+    }).collect()
+}
+
+fn floats<const N : usize>(json : &Value, key : &str, default : [f32; N]) -> Result<[f32; N], RadError>
+{
+    let Some(value) = json.get(key) else {
+        return Ok(default)
+    };
+
+    value.as_array()
+        .filter(|values| values.len() == N)
+        .and_then(|values| values.iter().map(|value| value.as_f64().map(|value| value as f32)).collect::<Option<Vec<_>>>())
+        .and_then(|values| values.try_into().ok())
+        .ok_or_else(|| parse_error(format!("{} should be {} numbers", key, N)))
+}
+
+fn float(json : &Value, key : &str, default : f32) -> Result<f32, RadError>
+{
+    match json.get(key)
+    {
+        Some(value) => value.as_f64().map(|value| value as f32).ok_or_else(|| parse_error(format!("{} should be a number", key))),
+        None => Ok(default),
+    }
+}
+
+fn parse_material(material : &Value) -> Result<GltfMaterial, RadError>
+{
+    let defaults = GltfMaterial::default();
+    let pbr = material.get("pbrMetallicRoughness").unwrap_or(&Value::Null);
+
+    Ok(GltfMaterial
+    {
+        name: name(material),
+        base_colour: floats(pbr, "baseColorFactor", defaults.base_colour)?,
+        metallic: float(pbr, "metallicFactor", defaults.metallic)?,
+        roughness: float(pbr, "roughnessFactor", defaults.roughness)?,
+        emissive: floats(material, "emissiveFactor", defaults.emissive)?,
+        double_sided: material.get("doubleSided").and_then(Value::as_bool).unwrap_or(false),
+        unlit: material.get("extensions").and_then(|extensions| extensions.get("KHR_materials_unlit")).is_some(),
+    })
+}
+
+fn parse_node(node : &Value, mesh_count : usize) -> Result<GltfNode, RadError>
+{
+    let matrix = match node.get("matrix")
+    {
+        Some(_) => {
+            let values : [f32; 16] = floats(node, "matrix", [0.0; 16])?;
+            [0, 1, 2, 3].map(|column| [0, 1, 2, 3].map(|row| values[column * 4 + row]))
+        },
+        None => {
+            let [tx, ty, tz] = floats(node, "translation", [0.0; 3])?;
+            let [x, y, z, w] = floats(node, "rotation", [0.0, 0.0, 0.0, 1.0])?;
+            let [sx, sy, sz] = floats(node, "scale", [1.0; 3])?;
+
+            // Translation * rotation * scale, with the rotation matrix from the unit quaternion
+            [
+                [(1.0 - 2.0 * (y * y + z * z)) * sx, 2.0 * (x * y + z * w) * sx, 2.0 * (x * z - y * w) * sx, 0.0],
+                [2.0 * (x * y - z * w) * sy, (1.0 - 2.0 * (x * x + z * z)) * sy, 2.0 * (y * z + x * w) * sy, 0.0],
+                [2.0 * (x * z + y * w) * sz, 2.0 * (y * z - x * w) * sz, (1.0 - 2.0 * (x * x + y * y)) * sz, 0.0],
+                [tx, ty, tz, 1.0],
+            ]
+        },
+    };
+
+    // Children are checked against the node count once every node is read, see check_hierarchy
+    let children = array(node, "children")?.iter()
+        .map(|child| as_usize(child).ok_or_else(|| parse_error(format!("Child {} isn't a node index", child))))
+        .collect::<Result<_, _>>()?;
+
+    Ok(GltfNode { name: name(node), matrix, mesh: index(node, "mesh", mesh_count)?, children })
+}
+
+/// Scenes are trees, so every node can have at most one parent and can't be its own ancestor. Returns each node's parent.
+fn check_hierarchy(nodes : &[GltfNode]) -> Result<Vec<Option<usize>>, RadError>
+{
+    let mut parents = vec![None; nodes.len()];
+
+    for (parent, node) in nodes.iter().enumerate()
+    {
+        for child in &node.children
+        {
+            match parents.get_mut(*child)
+            {
+                Some(Some(_)) => return Err(parse_error(format!("Node {} has more than one parent", child))),
+                Some(slot) => *slot = Some(parent),
+                None => return Err(parse_error(format!("Node {} has child {} which doesn't exist", parent, child))),
+            }
+        }
+    }
+
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark
+    {
+        Unvisited,
+        OnPath,
+        ReachesRoot,
+    }
+
+    // Each node is walked through once, later walks stop at the first node already known to reach a root
+    let mut marks = vec![Mark::Unvisited; nodes.len()];
+    let mut path = vec![];
+    for start in 0..nodes.len()
+    {
+        let mut node = Some(start);
+        while let Some(current) = node
+        {
+            match marks[current]
+            {
+                Mark::ReachesRoot => break,
+                Mark::OnPath => return Err(parse_error(format!("Node {} is its own ancestor", current))),
+                Mark::Unvisited => {
+                    marks[current] = Mark::OnPath;
+                    path.push(current);
+                    node = parents[current];
+                },
+            }
+        }
+
+        for node in path.drain(..)
+        {
+            marks[node] = Mark::ReachesRoot;
+        }
+    }
+
+    Ok(parents)
+}
+
+/// Transforms only move, rotate and scale evenly, and aren't inherited, so every node has to be no more than that once
+/// its parents are applied. Nodes that end up sheared, mirrored or scaled unevenly would be spawned wrong.
+fn check_transforms(nodes : &[GltfNode], parents : &[Option<usize>]) -> Result<(), RadError>
+{
+    let mut stack : Vec<(usize, [[f32; 4]; 4])> = (0..nodes.len())
+        .filter(|node| parents[*node].is_none())
+        .map(|root| (root, IDENTITY))
+        .collect();
+
+    while let Some((index, parent_matrix)) = stack.pop()
+    {
+        let matrix = multiply(parent_matrix, nodes[index].matrix);
+        if !is_similarity(matrix)
+        {
+            return Err(parse_error(format!("Node {} is sheared, mirrored or scaled unevenly, which a Transform can't represent", index)))
+        }
+
+        stack.extend(nodes[index].children.iter().map(|child| (*child, matrix)));
+    }
+
+    Ok(())
+}
+
+/// Whether a column major matrix only moves, rotates and scales evenly
+fn is_similarity(matrix : [[f32; 4]; 4]) -> bool
+{
+    let axes = [0, 1, 2].map(|column| [matrix[column][0], matrix[column][1], matrix[column][2]]);
+    let dot = |a : [f32; 3], b : [f32; 3]| a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+    let lengths = axes.map(|axis| dot(axis, axis).sqrt());
+
+    let scale = lengths.into_iter().fold(0.0, f32::max);
+    let tolerance = scale * 1e-3;
+    let [x, y, z] = axes;
+    let determinant = dot([x[1] * y[2] - x[2] * y[1], x[2] * y[0] - x[0] * y[2], x[0] * y[1] - x[1] * y[0]], z);
+
+    lengths.iter().all(|length| scale - length <= tolerance)
+        && [(x, y), (x, z), (y, z)].iter().all(|(a, b)| dot(*a, *b).abs() <= tolerance * scale)
+        && determinant >= 0.0
+}
+
+struct BufferView
+{
+    buffer : usize,
+    offset : usize,
+    length : usize,
+    /// None for tightly packed elements
+    stride : Option<usize>,
+}
+
+impl BufferView
+{
+    fn parse(view : &Value, buffers : &[Vec<u8>]) -> Result<Self, RadError>
+    {
+        let buffer = index(view, "buffer", buffers.len())?.ok_or_else(|| parse_error(String::from("Buffer view has no buffer")))?;
+        let offset = view.get("byteOffset").and_then(as_usize).unwrap_or(0);
+        let length = view.get("byteLength").and_then(as_usize).ok_or_else(|| parse_error(String::from("Buffer view has no byteLength")))?;
+
+        if offset.checked_add(length).filter(|end| *end <= buffers[buffer].len()).is_none()
+        {
+            return Err(parse_error(format!("Buffer view reaches past the end of buffer {}", buffer)))
+        }
+
+        Ok(Self { buffer, offset, length, stride: view.get("byteStride").and_then(as_usize) })
+    }
+}
+
+/// Everything needed to read accessors
+struct Document<'a>
+{
+    json : &'a Value,
+    buffers : Vec<Vec<u8>>,
+    views : Vec<BufferView>,
+}
+
+impl Document<'_>
+{
+    /// Every component of accessor `index` as f32, `components` per element.
+    /// Normalized integers are mapped to 0 to 1 or -1 to 1, other integers are converted as they are.
+    fn accessor(&self, index : usize, components : usize) -> Result<Vec<f32>, RadError>
+    {
+        let (accessor, component_type, count) = self.accessor_info(index, components)?;
+        let normalized = accessor.get("normalized").and_then(Value::as_bool).unwrap_or(false);
+
+        let convert : fn(&[u8], bool) -> f32 = match component_type
+        {
+            5120 => |bytes, normalized| if normalized { (bytes[0] as i8 as f32 / 127.0).max(-1.0) } else { bytes[0] as i8 as f32 },
+            5121 => |bytes, normalized| if normalized { bytes[0] as f32 / 255.0 } else { bytes[0] as f32 },
+            5122 => |bytes, normalized| {
+                let value = i16::from_le_bytes([bytes[0], bytes[1]]) as f32;
+                if normalized { (value / 32767.0).max(-1.0) } else { value }
+            },
+            5123 => |bytes, normalized| {
+                let value = u16::from_le_bytes([bytes[0], bytes[1]]) as f32;
+                if normalized { value / 65535.0 } else { value }
+            },
+            5125 => |bytes, _| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32,
+            5126 => |bytes, _| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            other => return Err(parse_error(format!("Accessor {} has unknown componentType {}", index, other))),
+        };
+
+        let elements = self.elements(index, accessor, component_type, components, count)?;
+        let mut values = Vec::with_capacity(elements.len());
+        elements.for_each(|component| values.push(convert(component, normalized)));
+        Ok(values)
+    }
+
+    /// An index accessor, which has to be unsigned scalars
+    fn indices(&self, index : usize) -> Result<Vec<u32>, RadError>
+    {
+        let (accessor, component_type, count) = self.accessor_info(index, 1)?;
+
+        let convert : fn(&[u8]) -> u32 = match component_type
+        {
+            5121 => |bytes| bytes[0] as u32,
+            5123 => |bytes| u16::from_le_bytes([bytes[0], bytes[1]]) as u32,
+            5125 => |bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            other => return Err(parse_error(format!("Index accessor {} has componentType {}, which isn't an unsigned integer", index, other))),
+        };
+
+        let elements = self.elements(index, accessor, component_type, 1, count)?;
+        let mut values = Vec::with_capacity(elements.len());
+        elements.for_each(|component| values.push(convert(component)));
+        Ok(values)
+    }
+
+    /// The accessor's JSON, component type and element count, checking it has `components` per element
+    fn accessor_info(&self, index : usize, components : usize) -> Result<(&Value, u64, usize), RadError>
+    {
+        let accessor = array(self.json, "accessors")?.get(index).ok_or_else(|| parse_error(format!("Accessor {} doesn't exist", index)))?;
+
+        if accessor.get("sparse").is_some()
+        {
+            return Err(parse_error(format!("Accessor {} is sparse, which isn't supported", index)))
+        }
+
+        let actual = match accessor.get("type").and_then(Value::as_str)
+        {
+            Some("SCALAR") => 1,
+            Some("VEC2") => 2,
+            Some("VEC3") => 3,
+            Some("VEC4") => 4,
+            other => return Err(parse_error(format!("Accessor {} has type {}, expected {} components", index, other.unwrap_or("unknown"), components))),
+        };
+        if actual != components
+        {
+            return Err(parse_error(format!("Accessor {} has {} components, expected {}", index, actual, components)))
+        }
+
+        let component_type = accessor.get("componentType").and_then(Value::as_u64).unwrap_or(0);
+        let count = accessor.get("count").and_then(as_usize).ok_or_else(|| parse_error(format!("Accessor {} has no count", index)))?;
+
+        Ok((accessor, component_type, count))
+    }
+
+    /// The accessor's elements, checked to fit in its buffer view before anything is read or allocated
+    fn elements(&self, accessor_index : usize, accessor : &Value, component_type : u64, components : usize, count : usize) -> Result<Elements<'_>, RadError>
+    {
+        let size = match component_type
+        {
+            5120 | 5121 => 1,
+            5122 | 5123 => 2,
+            _ => 4,
+        };
+        let element_size = size * components;
+
+        let Some(view) = index(accessor, "bufferView", self.views.len())? else {
+            if count > MAX_ZEROED_ELEMENTS
+            {
+                return Err(parse_error(format!("Accessor {} has {} elements but no buffer view, at most {} are allowed", accessor_index, count, MAX_ZEROED_ELEMENTS)))
+            }
+            return Ok(Elements { bytes: None, offset: 0, stride: element_size, size, components, count })
+        };
+
+        let view = &self.views[view];
+        let offset = accessor.get("byteOffset").and_then(as_usize).unwrap_or(0);
+        let stride = view.stride.unwrap_or(element_size);
+
+        // Overlapping elements would let a small view claim any number of them
+        if stride < element_size
+        {
+            return Err(parse_error(format!("Accessor {} has {} byte elements only {} bytes apart", accessor_index, element_size, stride)))
+        }
+
+        // Where the last element ends
+        let end = match count.checked_sub(1)
+        {
+            Some(last) => last.checked_mul(stride).and_then(|start| start.checked_add(offset)).and_then(|start| start.checked_add(element_size)),
+            None => Some(0),
+        };
+
+        if end.filter(|end| *end <= view.length).is_none()
+        {
+            return Err(parse_error(format!("Accessor {} reaches past the end of its buffer view", accessor_index)))
+        }
+
+        let bytes = &self.buffers[view.buffer][view.offset..view.offset + view.length];
+        Ok(Elements { bytes: Some(bytes), offset, stride, size, components, count })
+    }
+
+    fn mesh(&self, mesh : &Value, material_count : usize) -> Result<GltfMesh<MeshBuilder>, RadError>
+    {
+        let primitives = array(mesh, "primitives")?.iter()
+            .map(|primitive| self.primitive(primitive, material_count))
+            .collect::<Result<_, _>>()?;
+
+        Ok(GltfMesh { name: name(mesh), primitives })
+    }
+
+    fn primitive(&self, primitive : &Value, material_count : usize) -> Result<GltfPrimitive<MeshBuilder>, RadError>
+    {
+        let attributes = primitive.get("attributes").unwrap_or(&Value::Null);
+        let attribute = |name : &str, components : usize| -> Result<Option<Vec<f32>>, RadError>
+        {
+            match attributes.get(name)
+            {
+                Some(accessor) => {
+                    let accessor = as_usize(accessor).ok_or_else(|| parse_error(format!("{} isn't an accessor index", name)))?;
+                    self.accessor(accessor, components).map(Some)
+                },
+                None => Ok(None),
+            }
+        };
+
+        let positions = attribute("POSITION", 3)?.ok_or_else(|| parse_error(String::from("Primitive has no POSITION attribute")))?;
+        let normals = attribute("NORMAL", 3)?;
+        let uvs = attribute("TEXCOORD_0", 2)?;
+        let tangents = attribute("TANGENT", 4)?;
+
+        let vertex_count = positions.len() / 3;
+        for (name, values, components) in [("NORMAL", &normals, 3), ("TEXCOORD_0", &uvs, 2), ("TANGENT", &tangents, 4)]
+        {
+            match values
+            {
+                Some(values) if values.len() != vertex_count * components =>
+                    return Err(parse_error(format!("Primitive has {} {} values for {} vertices", values.len() / components, name, vertex_count))),
+                _ => (),
+            }
+        }
+
+        let vertices = (0..vertex_count).map(|vertex| Vertex
+        {
+            position: [0, 1, 2].map(|axis| positions[vertex * 3 + axis]),
+            normal: normals.as_ref().map_or([0.0; 3], |normals| [0, 1, 2].map(|axis| normals[vertex * 3 + axis])),
+            uv: uvs.as_ref().map_or([0.0; 2], |uvs| [uvs[vertex * 2], 1.0 - uvs[vertex * 2 + 1]]),
+        }).collect();
+
+        let corners = match primitive.get("indices")
+        {
+            Some(accessor) => Some(self.indices(as_usize(accessor).ok_or_else(|| parse_error(String::from("indices isn't an accessor index")))?)?),
+            None => None,
+        };
+
+        if let Some(index) = corners.iter().flatten().find(|index| **index as usize >= vertex_count)
+        {
+            return Err(parse_error(format!("Index {} is out of range for {} vertices", index, vertex_count)))
+        }
+
+        let sequential = || (0..vertex_count as u32).collect::<Vec<_>>();
+        let corners = match primitive.get("mode").map_or(Some(4), as_usize)
+        {
+            Some(4) => corners.map(|mut corners| {
+                corners.truncate(corners.len() / 3 * 3);
+                corners
+            }),
+            Some(5) => {
+                let strip = corners.unwrap_or_else(sequential);
+                Some((0..strip.len().saturating_sub(2)).flat_map(|i| [strip[i], strip[i + 1 + i % 2], strip[i + 2 - i % 2]]).collect())
+            },
+            Some(6) => {
+                let fan = corners.unwrap_or_else(sequential);
+                Some((1..fan.len().saturating_sub(1)).flat_map(|i| [fan[i], fan[i + 1], fan[0]]).collect())
+            },
+            mode => return Err(parse_error(format!("Primitive mode {:?} isn't supported, only triangles are", mode))),
+        };
+
+        let mut mesh = MeshBuilder::new(vertices, corners.map(Indices::new));
+        if mesh.indices.is_none()
+        {
+            mesh.vertices.truncate(vertex_count / 3 * 3);
+        }
+
+        // Flipping v turns the bitangent around, so the handedness flips with it
+        mesh.tangents = tangents.map(|tangents| tangents.chunks_exact(4).take(mesh.vertices.len()).map(|tangent| [tangent[0], tangent[1], tangent[2], -tangent[3]]).collect());
+
+        // The spec asks for flat normals when a primitive doesn't have any
+        if normals.is_none()
+        {
+            mesh.compute_flat_normals()?;
+        }
+
+        Ok(GltfPrimitive { mesh, material: index(primitive, "material", material_count)? })
+    }
+}
+
+
+/// An accessor's elements, already checked to lie inside its buffer view
+struct Elements<'a>
+{
+    /// None for accessors without a buffer view, which are all zeros
+    bytes : Option<&'a [u8]>,
+    offset : usize,
+    stride : usize,
+    /// Bytes per component
+    size : usize,
+    components : usize,
+    count : usize,
+}
+
+impl Elements<'_>
+{
+    /// How many components there are in total
+    fn len(&self) -> usize
+    {
+        self.count * self.components
+    }
+
+    /// Calls `component` with the bytes of every component in order
+    fn for_each(self, mut component : impl FnMut(&[u8]))
+    {
+        let Some(bytes) = self.bytes else {
+            let zeros = [0; 4];
+            (0..self.len()).for_each(|_| component(&zeros[..self.size]));
+            return
+        };
+
+        let element_size = self.size * self.components;
+        for element in 0..self.count
+        {
+            let start = self.offset + element * self.stride;
+            bytes[start..start + element_size].chunks_exact(self.size).for_each(&mut component);
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use std::error::Error;
+
+use crate::{AssetLoader, CpuAsset, Gltf, LoadContext, Mesh, OGLMesh};
+
+/// Loads `.gltf` and `.glb` files. Every primitive is also a mesh of its own, loadable as `file#Mesh0/Primitive0`.
+/// Buffers in separate files are read from the same [`AssetSource`](crate::AssetSource), but don't trigger a hot reload when they change.
+pub struct GltfLoader;
+
+impl AssetLoader for GltfLoader
+{
+    type Asset = Gltf;
+
+    fn extensions(&self) -> &[&str]
+    {
+        &["gltf", "glb"]
+    }
+
+    fn load(&self, bytes : Vec<u8>, context : &mut LoadContext) -> Result<Gltf, Box<dyn Error + Send + Sync>>
+    {
+        let gltf = Gltf::from_slice(&bytes, |uri| Ok(context.read(uri)?))?;
+
+        Ok(gltf.map_meshes(|mesh, primitive, builder|
+        {
+            Ok(Mesh { handle: context.add_labeled_asset::<OGLMesh>(&format!("Mesh{}/Primitive{}", mesh, primitive), builder) })
+        })?)
+    }
+}
+
+impl CpuAsset for Gltf {}
@@ -0,0 +1,3 @@
+import!(document);
+import!(loader);
+import!(scene);
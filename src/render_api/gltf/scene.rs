@@ -0,0 +1,78 @@
+use bevy_ecs::{entity::Entity, world::World};
+
+use crate::{Children, DefaultMaterial, Gltf, Name, Parent, Transform};
+
+use super::document::{multiply, IDENTITY};
+
+impl Gltf
+{
+    /// Spawns the nodes of a scene, picked like [`Gltf::scene`], returning the root entities or None if there's no such scene.
+    ///
+    /// Every node becomes an entity with its [`Transform`] in world space, since transforms aren't inherited,
+    /// and a [`Name`], [`Parent`] and [`Children`] where it has them.
+    /// Each primitive of a node's mesh is spawned as a child of the node with the [`Mesh`](crate::Mesh), a [`DefaultMaterial`] and the node's Transform.
+    pub fn spawn_scene(&self, world : &mut World, scene : Option<usize>) -> Option<Vec<Entity>>
+    {
+        let scene = self.scene(scene)?;
+
+        // Nodes can nest arbitrarily deep, so they're walked with a stack rather than recursion
+        let mut stack : Vec<(usize, [[f32; 4]; 4], Entity)> = scene.nodes.iter().map(|node| self.spawn_node(world, *node, IDENTITY, None)).collect();
+        let roots = stack.iter().map(|(_, _, entity)| *entity).collect();
+
+        while let Some((index, matrix, entity)) = stack.pop()
+        {
+            let children : Vec<_> = self.nodes[index].children.iter().map(|child| self.spawn_node(world, *child, matrix, Some(entity))).collect();
+            if children.is_empty()
+            {
+                continue;
+            }
+
+            // After the node's primitives
+            let ids = children.iter().map(|(_, _, child)| *child);
+            match world.get_mut::<Children>(entity)
+            {
+                Some(mut existing) => existing.0.extend(ids),
+                None => { world.entity_mut(entity).insert(Children(ids.collect())); },
+            }
+
+            stack.extend(children);
+        }
+
+        Some(roots)
+    }
+
+    /// Spawns a node and its primitives, returning the node's index, its matrix in world space and its entity
+    fn spawn_node(&self, world : &mut World, index : usize, parent_matrix : [[f32; 4]; 4], parent : Option<Entity>) -> (usize, [[f32; 4]; 4], Entity)
+    {
+        let node = &self.nodes[index];
+        let matrix = multiply(parent_matrix, node.matrix);
+        let transform = Transform::from_matrix(matrix);
+
+        let mut entity = world.spawn(transform);
+        if let Some(name) = &node.name
+        {
+            entity.insert(Name(name.clone()));
+        }
+        if let Some(parent) = parent
+        {
+            entity.insert(Parent(parent));
+        }
+        let id = entity.id();
+
+        let primitives : Vec<Entity> = node.mesh.iter()
+            .flat_map(|mesh| &self.meshes[*mesh].primitives)
+            .map(|primitive|
+            {
+                let material = primitive.material.map_or_else(DefaultMaterial::default, |material| DefaultMaterial::from(&self.materials[material]));
+                world.spawn((primitive.mesh.clone(), material, transform, Parent(id))).id()
+            })
+            .collect();
+
+        if !primitives.is_empty()
+        {
+            world.entity_mut(id).insert(Children(primitives));
+        }
+
+        (index, matrix, id)
+    }
+}
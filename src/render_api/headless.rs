@@ -0,0 +1,71 @@
+use bevy_ecs::world::World;
+use glium::Program;
+
+use crate::{send_asset_events, Assets, BakedCameraInformation, HandleReserver, Mesh, MeshBuilder, OGLMesh, RadError, RenderAPI, Shader, ShaderSource, WeakHandle, GLSL};
+
+/// A RenderAPI that never touches the GPU.
+/// Meshes and shaders are kept CPU side behind the same handle types OpenGL uses, drawing is a no-op.
+pub struct Headless {
+    meshes: Assets<OGLMesh, MeshBuilder>,
+    /// Vertex and fragment source
+    shaders: Assets<Program, (String, String)>,
+}
+
+impl Headless {
+    pub fn new() -> Self {
+        let mut meshes = Assets::default();
+        let mut shaders = Assets::default();
+        meshes.enable_events();
+        shaders.enable_events();
+
+        Self { meshes, shaders }
+    }
+}
+
+impl Default for Headless {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RenderAPI for Headless {
+    fn try_create_mesh(&mut self, mesh_builder: MeshBuilder) -> Result<Mesh, RadError> {
+        mesh_builder.validate()?;
+        Ok(Mesh { handle: self.meshes.add_asset(mesh_builder) })
+    }
+
+    fn try_create_program(&mut self, shader: &ShaderSource<GLSL>) -> Result<Shader, RadError> {
+        let sources = (shader.vertex_source.clone(), shader.fragment_source.clone());
+        Ok(Shader { handle: self.shaders.add_asset(sources) })
+    }
+
+    fn mesh_reserver(&self) -> HandleReserver<OGLMesh> {
+        self.meshes.reserver()
+    }
+
+    fn upload_mesh(&mut self, handle: WeakHandle<OGLMesh>, mesh_builder: MeshBuilder) -> Result<(), RadError> {
+        mesh_builder.validate()?;
+        self.meshes.insert(handle, mesh_builder).map(|_| ()).map_err(|_| RadError::InvalidHandle)
+    }
+
+    fn program_reserver(&self) -> HandleReserver<Program> {
+        self.shaders.reserver()
+    }
+
+    fn upload_program(&mut self, handle: WeakHandle<Program>, shader: &ShaderSource<GLSL>) -> Result<(), RadError> {
+        let sources = (shader.vertex_source.clone(), shader.fragment_source.clone());
+        self.shaders.insert(handle, sources).map(|_| ()).map_err(|_| RadError::InvalidHandle)
+    }
+
+    fn draw(&mut self, _world: &mut World, _baked_camera: &BakedCameraInformation) -> Result<(), RadError> {
+        Ok(())
+    }
+
+    fn cleanup(&mut self, world: &mut World) {
+        self.meshes.cleanup();
+        self.shaders.cleanup();
+
+        send_asset_events(world, self.meshes.drain_events());
+        send_asset_events(world, self.shaders.drain_events());
+    }
+}
@@ -1,9 +1,16 @@
+use std::collections::HashSet;
+
+use bevy_ecs::{event::Event, system::Resource};
+use winit::{event::{ElementState, WindowEvent}, keyboard::{KeyCode, PhysicalKey}};
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub enum MouseButton {
     Middle,
     Left,
     Right,
 }
 
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum InputAction {
     Press,
     Release,
@@ -53,6 +60,204 @@ pub enum Key {
 
 impl Key
 {
+    pub fn from_key_code(code : KeyCode) -> Key
+    {
+        match code
+        {
+            KeyCode::Space => Key::Space,
+            KeyCode::Quote => Key::Apostrophe,
+            KeyCode::Comma => Key::Comma,
+            KeyCode::Minus => Key::Minus,
+            KeyCode::Period => Key::Period,
+            KeyCode::Slash => Key::Slash,
+            KeyCode::Numpad0 => Key::Numpad(0),
+            KeyCode::Numpad1 => Key::Numpad(1),
+            KeyCode::Numpad2 => Key::Numpad(2),
+            KeyCode::Numpad3 => Key::Numpad(3),
+            KeyCode::Numpad4 => Key::Numpad(4),
+            KeyCode::Numpad5 => Key::Numpad(5),
+            KeyCode::Numpad6 => Key::Numpad(6),
+            KeyCode::Numpad7 => Key::Numpad(7),
+            KeyCode::Numpad8 => Key::Numpad(8),
+            KeyCode::Numpad9 => Key::Numpad(9),
+            KeyCode::Semicolon => Key::Semicolon,
+            KeyCode::Equal => Key::Equal,
+            KeyCode::Digit0 => Key::Key('0'),
+            KeyCode::Digit1 => Key::Key('1'),
+            KeyCode::Digit2 => Key::Key('2'),
+            KeyCode::Digit3 => Key::Key('3'),
+            KeyCode::Digit4 => Key::Key('4'),
+            KeyCode::Digit5 => Key::Key('5'),
+            KeyCode::Digit6 => Key::Key('6'),
+            KeyCode::Digit7 => Key::Key('7'),
+            KeyCode::Digit8 => Key::Key('8'),
+            KeyCode::Digit9 => Key::Key('9'),
+            KeyCode::KeyA => Key::Key('a'),
+            KeyCode::KeyB => Key::Key('b'),
+            KeyCode::KeyC => Key::Key('c'),
+            KeyCode::KeyD => Key::Key('d'),
+            KeyCode::KeyE => Key::Key('e'),
+            KeyCode::KeyF => Key::Key('f'),
+            KeyCode::KeyG => Key::Key('g'),
+            KeyCode::KeyH => Key::Key('h'),
+            KeyCode::KeyI => Key::Key('i'),
+            KeyCode::KeyJ => Key::Key('j'),
+            KeyCode::KeyK => Key::Key('k'),
+            KeyCode::KeyL => Key::Key('l'),
+            KeyCode::KeyM => Key::Key('m'),
+            KeyCode::KeyN => Key::Key('n'),
+            KeyCode::KeyO => Key::Key('o'),
+            KeyCode::KeyP => Key::Key('p'),
+            KeyCode::KeyQ => Key::Key('q'),
+            KeyCode::KeyR => Key::Key('r'),
+            KeyCode::KeyS => Key::Key('s'),
+            KeyCode::KeyT => Key::Key('t'),
+            KeyCode::KeyU => Key::Key('u'),
+            KeyCode::KeyV => Key::Key('v'),
+            KeyCode::KeyW => Key::Key('w'),
+            KeyCode::KeyX => Key::Key('x'),
+            KeyCode::KeyY => Key::Key('y'),
+            KeyCode::KeyZ => Key::Key('z'),
+            KeyCode::BracketLeft => Key::LeftBracket,
+            KeyCode::BracketRight => Key::RightBracket,
+            KeyCode::Backslash => Key::Backslash,
+            KeyCode::Escape => Key::Escape,
+            KeyCode::Enter | KeyCode::NumpadEnter => Key::Enter,
+            KeyCode::Tab => Key::Tab,
+            KeyCode::Backspace => Key::Backspace,
+            KeyCode::ArrowUp => Key::Arrow(ArrowDirection::Up),
+            KeyCode::ArrowDown => Key::Arrow(ArrowDirection::Down),
+            KeyCode::ArrowLeft => Key::Arrow(ArrowDirection::Left),
+            KeyCode::ArrowRight => Key::Arrow(ArrowDirection::Right),
+            KeyCode::F1 => Key::Function(1),
+            KeyCode::F2 => Key::Function(2),
+            KeyCode::F3 => Key::Function(3),
+            KeyCode::F4 => Key::Function(4),
+            KeyCode::F5 => Key::Function(5),
+            KeyCode::F6 => Key::Function(6),
+            KeyCode::F7 => Key::Function(7),
+            KeyCode::F8 => Key::Function(8),
+            KeyCode::F9 => Key::Function(9),
+            KeyCode::F10 => Key::Function(10),
+            KeyCode::F11 => Key::Function(11),
+            KeyCode::F12 => Key::Function(12),
+            KeyCode::CapsLock => Key::CapsLock,
+            KeyCode::NumLock => Key::NumLock,
+            KeyCode::ShiftLeft => Key::LeftShift,
+            KeyCode::ControlLeft => Key::LeftControl,
+            KeyCode::AltLeft => Key::LeftAlt,
+            KeyCode::ShiftRight => Key::RightShift,
+            KeyCode::ControlRight => Key::RightControl,
+            KeyCode::AltRight => Key::RightAlt,
+            _ => Key::Unknown,
+        }
+    }
+}
+
+impl MouseButton
+{
+    pub fn from_winit(button : winit::event::MouseButton) -> Option<MouseButton>
+    {
+        match button
+        {
+            winit::event::MouseButton::Left => Some(MouseButton::Left),
+            winit::event::MouseButton::Right => Some(MouseButton::Right),
+            winit::event::MouseButton::Middle => Some(MouseButton::Middle),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Event, Clone, Copy, PartialEq, Debug)]
+pub struct KeyboardInput
+{
+    pub key : Key,
+    pub action : InputAction,
+}
+
+#[derive(Event, Clone, Copy, PartialEq, Debug)]
+pub struct MouseButtonInput
+{
+    pub button : MouseButton,
+    pub action : InputAction,
+    /// Cursor position when the button changed
+    pub position : (f32, f32),
+}
+
+/// Cursor position in physical pixels from the top left of the window
+#[derive(Event, Clone, Copy, PartialEq, Debug)]
+pub struct CursorMoved
+{
+    pub position : (f32, f32),
+}
+
+/// Current keyboard and mouse state, kept up to date by the App while it exists
+#[derive(Resource, Default)]
+pub struct Input
+{
+    keys : HashSet<Key>,
+    mouse_buttons : HashSet<MouseButton>,
+    mouse_position : (f32, f32),
+}
+
+impl Input
+{
+    pub fn is_key_down(&self, key : Key) -> bool
+    {
+        self.keys.contains(&key)
+    }
+
+    pub fn is_mouse_down(&self, button : MouseButton) -> bool
+    {
+        self.mouse_buttons.contains(&button)
+    }
+
+    /// Cursor position in physical pixels from the top left of the window
+    pub fn mouse_position(&self) -> (f32, f32)
+    {
+        self.mouse_position
+    }
+
+    pub(crate) fn handle_window_event(&mut self, event : &WindowEvent)
+    {
+        match event
+        {
+            WindowEvent::KeyboardInput { event, .. } =>
+            {
+                if let PhysicalKey::Code(code) = event.physical_key
+                {
+                    let key = Key::from_key_code(code);
+                    match event.state
+                    {
+                        ElementState::Pressed => self.keys.insert(key),
+                        ElementState::Released => self.keys.remove(&key),
+                    };
+                }
+            },
+            WindowEvent::MouseInput { state, button, .. } =>
+            {
+                if let Some(button) = MouseButton::from_winit(*button)
+                {
+                    match state
+                    {
+                        ElementState::Pressed => self.mouse_buttons.insert(button),
+                        ElementState::Released => self.mouse_buttons.remove(&button),
+                    };
+                }
+            },
+            WindowEvent::CursorMoved { position, .. } =>
+            {
+                self.mouse_position = (position.x as f32, position.y as f32);
+            },
+            WindowEvent::Focused(false) =>
+            {
+                // Releases won't be seen while unfocused
+                self.keys.clear();
+                self.mouse_buttons.clear();
+            },
+            _ => {}
+        }
+    }
 }
 
 impl InputAction
@@ -0,0 +1,60 @@
+use crate::MeshBuilder;
+
+/// Axis aligned box around a mesh, in the mesh's own space
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Aabb {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+}
+
+impl Aabb {
+    pub fn centre(&self) -> [f32; 3] {
+        [0, 1, 2].map(|axis| (self.min[axis] + self.max[axis]) * 0.5)
+    }
+
+    /// Half the size along each axis
+    pub fn half_extents(&self) -> [f32; 3] {
+        [0, 1, 2].map(|axis| (self.max[axis] - self.min[axis]) * 0.5)
+    }
+
+    pub fn contains(&self, point: [f32; 3]) -> bool {
+        (0..3).all(|axis| (self.min[axis]..=self.max[axis]).contains(&point[axis]))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BoundingSphere {
+    pub centre: [f32; 3],
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    pub fn contains(&self, point: [f32; 3]) -> bool {
+        (0..3).map(|axis| (point[axis] - self.centre[axis]).powi(2)).sum::<f32>() <= self.radius * self.radius
+    }
+}
+
+impl MeshBuilder {
+    /// None for a mesh without any vertices
+    pub fn aabb(&self) -> Option<Aabb> {
+        let first = self.vertices.first()?.position;
+
+        Some(self.vertices.iter().fold(Aabb { min: first, max: first }, |aabb, vertex| Aabb {
+            min: [0, 1, 2].map(|axis| aabb.min[axis].min(vertex.position[axis])),
+            max: [0, 1, 2].map(|axis| aabb.max[axis].max(vertex.position[axis])),
+        }))
+    }
+
+    /// Centred on the [`MeshBuilder::aabb`] and reaching the furthest vertex, so it's cheap and always holds the whole mesh
+    /// but can be a little larger than the smallest sphere that would
+    pub fn bounding_sphere(&self) -> Option<BoundingSphere> {
+        let centre = self.aabb()?.centre();
+
+        let radius = self.vertices.iter()
+            .map(|vertex| (0..3).map(|axis| (vertex.position[axis] - centre[axis]).powi(2)).sum::<f32>())
+            .fold(0.0, f32::max)
+            .sqrt();
+
+        Some(BoundingSphere { centre, radius })
+    }
+}
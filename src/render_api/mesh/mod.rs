@@ -1,9 +1,13 @@
-use std::io::{BufReader, Cursor};
+use std::{collections::HashMap, error::Error, io::{self, BufReader, Cursor}};
 
 use bevy_ecs::component::Component;
 use glium::implement_vertex;
 
-use crate::AssetHandle;
+import!(primitives);
+import!(processing);
+import!(bounds);
+
+use crate::{read_u16, read_u32, read_u8, AssetLoader, LoadContext, OGLMesh, RadError};
 
 #[derive(Clone, Copy)]
 pub struct Vertex {
@@ -14,12 +18,87 @@ pub struct Vertex {
 
 implement_vertex!(Vertex, position, normal, uv);
 
+/// Per-vertex tangent in a second vertex buffer, only there for meshes with [`MeshBuilder::tangents`].
+/// `w` is 1 or -1, the bitangent is `cross(normal, tangent.xyz) * w`.
+#[derive(Clone, Copy)]
+pub struct Tangent {
+    pub tangent: [f32; 4],
+}
+
+implement_vertex!(Tangent, tangent);
+
+/// Triangle list indices, kept as u16 whenever every index fits to halve their size on the GPU
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Indices {
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+}
+
+impl Indices {
+    /// Picks the narrowest width that fits every index
+    pub fn new(indices: Vec<u32>) -> Self {
+        if indices.iter().all(|index| *index <= u16::MAX as u32) {
+            Indices::U16(indices.into_iter().map(|index| index as u16).collect())
+        } else {
+            Indices::U32(indices)
+        }
+    }
+
+    /// `0..count`, for drawing vertices in order
+    pub fn sequential(count: usize) -> Result<Self, RadError> {
+        let count = u32::try_from(count)
+            .map_err(|_| RadError::MeshUpload(format!("{} vertices is more than 32 bit indices can address", count)))?;
+        Ok(Indices::new((0..count).collect()))
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Indices::U16(indices) => indices.len(),
+            Indices::U32(indices) => indices.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        let (narrow, wide) = match self {
+            Indices::U16(indices) => (indices.as_slice(), &[][..]),
+            Indices::U32(indices) => (&[][..], indices.as_slice()),
+        };
+        narrow.iter().map(|index| *index as u32).chain(wide.iter().copied())
+    }
+}
+
+impl From<Vec<u16>> for Indices {
+    fn from(indices: Vec<u16>) -> Self {
+        Indices::U16(indices)
+    }
+}
+
+/// Narrows to u16 when every index fits, see [`Indices::new`]
+impl From<Vec<u32>> for Indices {
+    fn from(indices: Vec<u32>) -> Self {
+        Indices::new(indices)
+    }
+}
+
 pub struct MeshBuilder {
     pub vertices: Vec<Vertex>,
-    pub indices: Option<Vec<u16>>,
+    /// None draws the vertices in order
+    pub indices: Option<Indices>,
+    /// One per vertex when there are any, see [`MeshBuilder::generate_tangents`]
+    pub tangents: Option<Vec<[f32; 4]>>,
 }
 
 impl MeshBuilder {
+    pub fn new(vertices: Vec<Vertex>, indices: Option<Indices>) -> MeshBuilder {
+        MeshBuilder { vertices, indices, tangents: None }
+    }
+
+    /// Parses an OBJ file, welding face corners with the same position, normal and uv into one indexed vertex.
+    /// Files without any vn lines get smooth normals, since zero normals would leave them unlit.
     pub fn from_obj<S: AsRef<str>>(contents: S) -> MeshBuilder {
         let mut obj = prospect_obj::parse_obj(contents);
 
@@ -37,10 +116,220 @@ impl MeshBuilder {
             )
         }
 
-        MeshBuilder
+        let mut mesh = MeshBuilder::welded(verts);
+        if mesh.vertices.iter().all(|vertex| vertex.normal == [0.0; 3])
+        {
+            // Welding only makes indices that point at its own vertices
+            mesh.smooth_normals();
+        }
+
+        mesh
+    }
+
+    /// Indexes a list of triangle corners, sharing one vertex between every corner that's exactly the same
+    fn welded(corners: Vec<Vertex>) -> MeshBuilder {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::with_capacity(corners.len());
+        // Keyed on the bits so NaNs can't break the lookup
+        let mut welded : HashMap<[u32; 8], u32> = HashMap::new();
+
+        for vertex in &corners
+        {
+            let [x, y, z] = vertex.position;
+            let [nx, ny, nz] = vertex.normal;
+            let [u, v] = vertex.uv;
+            let key = [x, y, z, nx, ny, nz, u, v].map(f32::to_bits);
+
+            let index = match welded.get(&key)
+            {
+                Some(index) => *index,
+                None => {
+                    // Too many unique vertices to index, leave the mesh unindexed
+                    let Ok(index) = u32::try_from(vertices.len()) else {
+                        return MeshBuilder::new(corners, None)
+                    };
+                    welded.insert(key, index);
+                    vertices.push(*vertex);
+                    index
+                },
+            };
+
+            indices.push(index);
+        }
+
+        MeshBuilder::new(vertices, Some(Indices::new(indices)))
+    }
+
+    /// Checks the mesh can be drawn, which needs every index to point at a vertex and a tangent for every vertex if there are any
+    pub fn validate(&self) -> Result<(), RadError> {
+        if let Some(tangents) = self.tangents.as_ref().filter(|tangents| tangents.len() != self.vertices.len()) {
+            return Err(RadError::MeshUpload(format!("{} tangents for {} vertices", tangents.len(), self.vertices.len())))
+        }
+
+        match &self.indices {
+            Some(indices) => match indices.iter().find(|index| *index as usize >= self.vertices.len()) {
+                Some(index) => Err(RadError::MeshUpload(format!("Index {} is out of range for {} vertices", index, self.vertices.len()))),
+                None => Ok(()),
+            },
+            None => Indices::sequential(self.vertices.len()).map(|_| ()),
+        }
+    }
+
+    /// Packs the mesh into a little endian binary form, read back with [`MeshBuilder::from_bytes`].
+    /// Fails for meshes with more vertices or indices than the form can count.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, RadError> {
+        let count = |len : usize, what : &str| u32::try_from(len)
+            .map(u32::to_le_bytes)
+            .map_err(|_| RadError::Io(io::Error::new(io::ErrorKind::InvalidInput, format!("{} {} is too many to pack a mesh", len, what))));
+
+        let mut bytes = Vec::with_capacity(4 + self.vertices.len() * 32 + 5 + self.indices.as_ref().map_or(0, |indices| indices.len() * 4));
+
+        bytes.extend_from_slice(&count(self.vertices.len(), "vertices")?);
+        for vertex in &self.vertices
+        {
+            for value in vertex.position.iter().chain(&vertex.normal).chain(&vertex.uv)
+            {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+
+        match &self.indices
         {
-            vertices: verts,
-            indices: None,
+            Some(Indices::U16(indices)) => {
+                bytes.push(1);
+                bytes.extend_from_slice(&count(indices.len(), "indices")?);
+                for index in indices
+                {
+                    bytes.extend_from_slice(&index.to_le_bytes());
+                }
+            },
+            Some(Indices::U32(indices)) => {
+                bytes.push(2);
+                bytes.extend_from_slice(&count(indices.len(), "indices")?);
+                for index in indices
+                {
+                    bytes.extend_from_slice(&index.to_le_bytes());
+                }
+            },
+            None => bytes.push(0),
         }
+
+        if let Some(tangents) = &self.tangents
+        {
+            bytes.push(1);
+            for value in tangents.iter().flatten()
+            {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+
+        Ok(bytes)
     }
-}
\ No newline at end of file
+
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<MeshBuilder, RadError> {
+        let reader = &mut bytes;
+
+        let vertex_count = read_u32(reader)? as usize;
+        // Checked up front so a corrupt count can't ask for a huge allocation, saturating so it can't wrap around on 32 bit targets either
+        if reader.len() < vertex_count.saturating_mul(32)
+        {
+            return Err(RadError::Parse(String::from("Mesh data ends before its last vertex")))
+        }
+
+        let mut vertices = Vec::with_capacity(vertex_count);
+        for _ in 0..vertex_count
+        {
+            let mut values = [0.0; 8];
+            for value in &mut values
+            {
+                *value = f32::from_bits(read_u32(reader)?);
+            }
+
+            vertices.push(Vertex {
+                position: [values[0], values[1], values[2]],
+                normal: [values[3], values[4], values[5]],
+                uv: [values[6], values[7]],
+            });
+        }
+
+        let width = match read_u8(reader)?
+        {
+            0 => None,
+            1 => Some(2),
+            2 => Some(4),
+            other => return Err(RadError::Parse(format!("Unknown mesh index type {}", other))),
+        };
+
+        let indices = match width
+        {
+            Some(width) => {
+                let index_count = read_u32(reader)? as usize;
+                if reader.len() < index_count.saturating_mul(width)
+                {
+                    return Err(RadError::Parse(String::from("Mesh data ends before its last index")))
+                }
+
+                Some(match width
+                {
+                    2 => Indices::U16((0..index_count).map(|_| read_u16(reader)).collect::<Result<_, _>>()?),
+                    _ => Indices::U32((0..index_count).map(|_| read_u32(reader)).collect::<Result<_, _>>()?),
+                })
+            },
+            None => None,
+        };
+
+        // Tangents are optional and come last
+        let tangents = match reader.first()
+        {
+            Some(_) => {
+                read_u8(reader)?;
+                if reader.len() < vertex_count.saturating_mul(16)
+                {
+                    return Err(RadError::Parse(String::from("Mesh data ends before its last tangent")))
+                }
+
+                let mut tangents = Vec::with_capacity(vertex_count);
+                for _ in 0..vertex_count
+                {
+                    let mut tangent = [0.0; 4];
+                    for value in &mut tangent
+                    {
+                        *value = f32::from_bits(read_u32(reader)?);
+                    }
+                    tangents.push(tangent);
+                }
+                Some(tangents)
+            },
+            None => None,
+        };
+
+        Ok(MeshBuilder { vertices, indices, tangents })
+    }
+}
+
+/// Loads Wavefront OBJ files as meshes
+pub struct ObjLoader;
+
+impl AssetLoader for ObjLoader {
+    type Asset = OGLMesh;
+
+    fn extensions(&self) -> &[&str] {
+        &["obj"]
+    }
+
+    fn load(&self, bytes: Vec<u8>, _context: &mut LoadContext) -> Result<MeshBuilder, Box<dyn Error + Send + Sync>> {
+        Ok(MeshBuilder::from_obj(String::from_utf8(bytes)?))
+    }
+
+    fn processed_format(&self) -> Option<&str> {
+        Some("rad.mesh.1")
+    }
+
+    fn process(&self, loaded: &MeshBuilder) -> Option<Vec<u8>> {
+        loaded.to_bytes().ok()
+    }
+
+    fn load_processed(&self, bytes: Vec<u8>) -> Result<MeshBuilder, Box<dyn Error + Send + Sync>> {
+        Ok(MeshBuilder::from_bytes(&bytes)?)
+    }
+}
@@ -0,0 +1,312 @@
+use std::{collections::{HashMap, HashSet}, f32::consts::{PI, TAU}};
+
+use crate::{Indices, MeshBuilder, Vertex};
+
+// Every primitive is centred on the origin with y up, and winds counter-clockwise seen from outside.
+// Curved surfaces go around y starting at +x, turning towards -z, with u following the angle.
+
+fn vertex(position: [f32; 3], normal: [f32; 3], uv: [f32; 2]) -> Vertex {
+    Vertex { position, normal, uv }
+}
+
+/// Direction around y after `turns` of a full circle, see the note at the top.
+/// Whole turns land exactly back on +x so vertices along a seam end up in the same place.
+fn around(turns: f32) -> (f32, f32) {
+    let angle = turns.fract() * TAU;
+    (angle.cos(), -angle.sin())
+}
+
+/// Sine and cosine of the angle down from +y, with the sine kept from going negative at the bottom so both poles are exact
+fn polar(turns: f32) -> (f32, f32) {
+    let angle = turns * PI;
+    (angle.sin().max(0.0), angle.cos())
+}
+
+/// Joins each row of a grid of vertices to the one below it.
+/// With `poles` the first and last rows are single points, so the triangles that would collapse there are left out.
+fn stitch(indices: &mut Vec<u32>, first: u32, rows: u32, columns: u32, poles: bool) {
+    for row in 0..rows - 1 {
+        for column in 0..columns - 1 {
+            let top = first + row * columns + column;
+            let bottom = top + columns;
+
+            if !(poles && row == 0) {
+                indices.extend_from_slice(&[top, bottom, top + 1]);
+            }
+            if !(poles && row == rows - 2) {
+                indices.extend_from_slice(&[top + 1, bottom, bottom + 1]);
+            }
+        }
+    }
+}
+
+/// A flat disc facing up or down, as a fan around its centre
+fn disc(vertices: &mut Vec<Vertex>, indices: &mut Vec<u32>, radius: f32, y: f32, sectors: u32, up: bool) {
+    let centre = vertices.len() as u32;
+    let normal = [0.0, if up { 1.0 } else { -1.0 }, 0.0];
+    vertices.push(vertex([0.0, y, 0.0], normal, [0.5, 0.5]));
+
+    for sector in 0..sectors {
+        let (x, z) = around(sector as f32 / sectors as f32);
+        vertices.push(vertex([x * radius, y, z * radius], normal, [0.5 + x * 0.5, 0.5 + if up { -z } else { z } * 0.5]));
+    }
+
+    for sector in 0..sectors {
+        let (a, b) = (centre + 1 + sector, centre + 1 + (sector + 1) % sectors);
+        indices.extend_from_slice(&if up { [centre, a, b] } else { [centre, b, a] });
+    }
+}
+
+/// One subdivided square face of side `size`, pushed `offset` out along its normal. `u` cross `v` has to equal `normal`.
+fn face(vertices: &mut Vec<Vertex>, indices: &mut Vec<u32>, size: f32, (normal, u, v): ([f32; 3], [f32; 3], [f32; 3]), subdivisions: u32, offset: f32) {
+    let first = vertices.len() as u32;
+
+    for row in 0..=subdivisions {
+        let y = 1.0 - row as f32 / subdivisions as f32;
+        for column in 0..=subdivisions {
+            let x = column as f32 / subdivisions as f32;
+            let position = [0, 1, 2].map(|axis| normal[axis] * offset + (u[axis] * (x - 0.5) + v[axis] * (y - 0.5)) * size);
+            vertices.push(vertex(position, normal, [x, y]));
+        }
+    }
+
+    stitch(indices, first, subdivisions + 1, subdivisions + 1, false);
+}
+
+fn build(vertices: Vec<Vertex>, indices: Vec<u32>) -> MeshBuilder {
+    MeshBuilder::new(vertices, Some(Indices::new(indices)))
+}
+
+impl MeshBuilder {
+    /// A square on the xz plane facing up, split into `subdivisions` quads along each side
+    pub fn plane(size: f32, subdivisions: u32) -> MeshBuilder {
+        let (mut vertices, mut indices) = (vec![], vec![]);
+        face(&mut vertices, &mut indices, size, ([0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]), subdivisions.max(1), 0.0);
+        build(vertices, indices)
+    }
+
+    /// A cube with flat faces, each split into `subdivisions` quads along each side and mapped to the whole texture
+    pub fn cube(size: f32, subdivisions: u32) -> MeshBuilder {
+        let subdivisions = subdivisions.max(1);
+        let (mut vertices, mut indices) = (vec![], vec![]);
+
+        let faces = [
+            ([1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]),
+            ([-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+            ([0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]),
+            ([0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+            ([0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+            ([0.0, 0.0, -1.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+        ];
+        for axes in faces {
+            face(&mut vertices, &mut indices, size, axes, subdivisions, size * 0.5);
+        }
+
+        build(vertices, indices)
+    }
+
+    /// A sphere of `sectors` slices around y and `stacks` bands from pole to pole, at least 3 and 2
+    pub fn uv_sphere(radius: f32, sectors: u32, stacks: u32) -> MeshBuilder {
+        let (sectors, stacks) = (sectors.max(3), stacks.max(2));
+        let mut vertices = Vec::with_capacity(((sectors + 1) * (stacks + 1)) as usize);
+        let mut indices = Vec::with_capacity((6 * sectors * (stacks - 1)) as usize);
+
+        for stack in 0..=stacks {
+            let (sin, cos) = polar(stack as f32 / stacks as f32);
+            for sector in 0..=sectors {
+                let (x, z) = around(sector as f32 / sectors as f32);
+                let normal = [x * sin, cos, z * sin];
+                vertices.push(vertex(normal.map(|n| n * radius), normal, [sector as f32 / sectors as f32, 1.0 - stack as f32 / stacks as f32]));
+            }
+        }
+
+        stitch(&mut indices, 0, stacks + 1, sectors + 1, true);
+        build(vertices, indices)
+    }
+
+    /// A sphere made by splitting each triangle of an icosahedron into 4, `subdivisions` times.
+    /// Triangles are far more even than a [`MeshBuilder::uv_sphere`]. Vertices on the texture seam are split, so u runs up to about 1.1
+    /// for the triangles across it, and each triangle at a pole has a pole vertex of its own.
+    pub fn ico_sphere(radius: f32, subdivisions: u32) -> MeshBuilder {
+        let t = (1.0 + 5f32.sqrt()) / 2.0;
+        let mut points: Vec<[f32; 3]> = vec![
+            [-1.0, t, 0.0], [1.0, t, 0.0], [-1.0, -t, 0.0], [1.0, -t, 0.0],
+            [0.0, -1.0, t], [0.0, 1.0, t], [0.0, -1.0, -t], [0.0, 1.0, -t],
+            [t, 0.0, -1.0], [t, 0.0, 1.0], [-t, 0.0, -1.0], [-t, 0.0, 1.0],
+        ];
+        let mut triangles: Vec<[u32; 3]> = vec![
+            [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+            [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+            [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+            [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+        ];
+
+        for _ in 0..subdivisions {
+            // Edges are shared by two triangles, so each midpoint is only made once
+            let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+            let mut midpoint = |a: u32, b: u32, points: &mut Vec<[f32; 3]>| {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    let (a, b) = (points[a as usize], points[b as usize]);
+                    points.push([0, 1, 2].map(|axis| (a[axis] + b[axis]) * 0.5));
+                    points.len() as u32 - 1
+                })
+            };
+
+            triangles = triangles.into_iter().flat_map(|[a, b, c]| {
+                let (ab, bc, ca) = (midpoint(a, b, &mut points), midpoint(b, c, &mut points), midpoint(c, a, &mut points));
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            }).collect();
+        }
+
+        let mut vertices: Vec<Vertex> = points.into_iter().map(|point| {
+            let length = point.iter().map(|p| p * p).sum::<f32>().sqrt();
+            let normal = point.map(|p| p / length);
+            let u = (-normal[2]).atan2(normal[0]).rem_euclid(TAU) / TAU;
+            let v = 0.5 + normal[1].clamp(-1.0, 1.0).asin() / PI;
+            vertex(normal.map(|n| n * radius), normal, [u, v])
+        }).collect();
+
+        // u has no single value at the poles, and jumps from 1 back to 0 across the seam
+        let is_pole = |vertex: &Vertex| vertex.normal[0].abs() < 1e-6 && vertex.normal[2].abs() < 1e-6;
+        let mut seam_copies: HashMap<u32, u32> = HashMap::new();
+        let mut poles_used: HashSet<u32> = HashSet::new();
+        let mut indices = Vec::with_capacity(triangles.len() * 3);
+
+        for mut triangle in triangles {
+            // Corners left behind by the seam get a copy past it, at u + 1, shared by every triangle across the seam
+            let highest = triangle.iter().map(|corner| &vertices[*corner as usize]).filter(|vertex| !is_pole(vertex)).map(|vertex| vertex.uv[0]).fold(0.0, f32::max);
+            for corner in &mut triangle {
+                let original = vertices[*corner as usize];
+                if !is_pole(&original) && highest - original.uv[0] > 0.5 {
+                    *corner = *seam_copies.entry(*corner).or_insert_with(|| {
+                        vertices.push(Vertex { uv: [original.uv[0] + 1.0, original.uv[1]], ..original });
+                        vertices.len() as u32 - 1
+                    });
+                }
+            }
+
+            // Like a uv_sphere's, each triangle gets its own pole at the u between its other two corners
+            for pole in 0..3 {
+                let original = vertices[triangle[pole] as usize];
+                if !is_pole(&original) {
+                    continue;
+                }
+
+                let others = [(pole + 1) % 3, (pole + 2) % 3].map(|other| vertices[triangle[other] as usize].uv[0]);
+                let uv = [(others[0] + others[1]) * 0.5, original.uv[1]];
+                if poles_used.insert(triangle[pole]) {
+                    vertices[triangle[pole] as usize].uv = uv;
+                } else {
+                    vertices.push(Vertex { uv, ..original });
+                    triangle[pole] = vertices.len() as u32 - 1;
+                }
+            }
+
+            indices.extend_from_slice(&triangle);
+        }
+
+        build(vertices, indices)
+    }
+
+    /// A capped cylinder along y, `sectors` slices around it (at least 3) and `segments` bands along its height
+    pub fn cylinder(radius: f32, height: f32, sectors: u32, segments: u32) -> MeshBuilder {
+        let (sectors, segments) = (sectors.max(3), segments.max(1));
+        let (mut vertices, mut indices) = (vec![], vec![]);
+
+        for segment in 0..=segments {
+            let v = 1.0 - segment as f32 / segments as f32;
+            for sector in 0..=sectors {
+                let (x, z) = around(sector as f32 / sectors as f32);
+                vertices.push(vertex([x * radius, (v - 0.5) * height, z * radius], [x, 0.0, z], [sector as f32 / sectors as f32, v]));
+            }
+        }
+        stitch(&mut indices, 0, segments + 1, sectors + 1, false);
+
+        disc(&mut vertices, &mut indices, radius, height * 0.5, sectors, true);
+        disc(&mut vertices, &mut indices, radius, -height * 0.5, sectors, false);
+        build(vertices, indices)
+    }
+
+    /// A cone along y with its point at the top and a capped base, `sectors` slices around it (at least 3)
+    pub fn cone(radius: f32, height: f32, sectors: u32) -> MeshBuilder {
+        let sectors = sectors.max(3);
+        let (mut vertices, mut indices) = (vec![], vec![]);
+
+        // The slope's normal leans up by the same amount the side leans in
+        let slope = (height * height + radius * radius).sqrt();
+        let normal = |turns: f32| {
+            let (x, z) = around(turns);
+            [x * height / slope, radius / slope, z * height / slope]
+        };
+
+        // One tip per sector so each gets the normal halfway across its sector
+        for sector in 0..sectors {
+            let turns = (sector as f32 + 0.5) / sectors as f32;
+            vertices.push(vertex([0.0, height * 0.5, 0.0], normal(turns), [turns, 1.0]));
+        }
+        for sector in 0..=sectors {
+            let turns = sector as f32 / sectors as f32;
+            let (x, z) = around(turns);
+            vertices.push(vertex([x * radius, -height * 0.5, z * radius], normal(turns), [turns, 0.0]));
+        }
+        for sector in 0..sectors {
+            indices.extend_from_slice(&[sector, sectors + sector, sectors + sector + 1]);
+        }
+
+        disc(&mut vertices, &mut indices, radius, -height * 0.5, sectors, false);
+        build(vertices, indices)
+    }
+
+    /// A cylinder of `depth` along y with a hemisphere on each end, `sectors` slices around it (at least 3)
+    /// and `rings` bands on each hemisphere
+    pub fn capsule(radius: f32, depth: f32, sectors: u32, rings: u32) -> MeshBuilder {
+        let (sectors, rings) = (sectors.max(3), rings.max(1));
+        let mut vertices = vec![];
+        let mut indices = vec![];
+        let length = depth + radius * 2.0;
+
+        // The bottom row of the top hemisphere and the top row of the bottom one are the ends of the cylinder
+        for (centre, start) in [(depth * 0.5, 0.0), (-depth * 0.5, 0.5)] {
+            for ring in 0..=rings {
+                let (sin, cos) = polar(start + ring as f32 / rings as f32 * 0.5);
+                let y = centre + cos * radius;
+                for sector in 0..=sectors {
+                    let (x, z) = around(sector as f32 / sectors as f32);
+                    let normal = [x * sin, cos, z * sin];
+                    vertices.push(vertex(
+                        [normal[0] * radius, y, normal[2] * radius],
+                        normal,
+                        [sector as f32 / sectors as f32, (y + length * 0.5) / length],
+                    ));
+                }
+            }
+        }
+
+        stitch(&mut indices, 0, (rings + 1) * 2, sectors + 1, true);
+        build(vertices, indices)
+    }
+
+    /// A ring around y, `major_segments` around the ring and `minor_segments` around its tube, at least 3 each
+    pub fn torus(major_radius: f32, minor_radius: f32, major_segments: u32, minor_segments: u32) -> MeshBuilder {
+        let (major_segments, minor_segments) = (major_segments.max(3), minor_segments.max(3));
+        let (mut vertices, mut indices) = (vec![], vec![]);
+
+        // Going round the tube from its outside edge underneath first, so each row starts off below the last
+        for minor in 0..=minor_segments {
+            let tube = -(minor as f32 / minor_segments as f32).fract() * TAU;
+            for major in 0..=major_segments {
+                let (x, z) = around(major as f32 / major_segments as f32);
+                let normal = [x * tube.cos(), tube.sin(), z * tube.cos()];
+                let position = [
+                    x * major_radius + normal[0] * minor_radius,
+                    normal[1] * minor_radius,
+                    z * major_radius + normal[2] * minor_radius,
+                ];
+                vertices.push(vertex(position, normal, [major as f32 / major_segments as f32, 1.0 - minor as f32 / minor_segments as f32]));
+            }
+        }
+
+        stitch(&mut indices, 0, minor_segments + 1, major_segments + 1, false);
+        build(vertices, indices)
+    }
+}
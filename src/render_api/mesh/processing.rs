@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+
+use crate::{Indices, MeshBuilder, RadError, Vertex};
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn add(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn scale(a: [f32; 3], by: f32) -> [f32; 3] {
+    a.map(|value| value * by)
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+/// Zero length vectors stay zero rather than turning into NaNs
+fn normalize(a: [f32; 3]) -> [f32; 3] {
+    let length = dot(a, a).sqrt();
+    if length > 0.0 { scale(a, 1.0 / length) } else { a }
+}
+
+/// Any unit vector at right angles to `normal`
+fn perpendicular(normal: [f32; 3]) -> [f32; 3] {
+    let axis = if normal[0].abs() < 0.9 { [1.0, 0.0, 0.0] } else { [0.0, 1.0, 0.0] };
+    normalize(cross(axis, normal))
+}
+
+impl MeshBuilder {
+    /// The vertex behind every triangle corner, in order
+    fn corners(&self) -> Vec<u32> {
+        match &self.indices {
+            Some(indices) => indices.iter().collect(),
+            None => (0..self.vertices.len() as u32).collect(),
+        }
+    }
+
+    /// Gives every triangle its own three vertices with the triangle's normal.
+    /// This leaves the mesh unindexed, since no corner can be shared once normals differ per triangle.
+    /// Fails without changing anything if the mesh doesn't pass [`MeshBuilder::validate`].
+    pub fn compute_flat_normals(&mut self) -> Result<(), RadError> {
+        self.validate()?;
+        let corners = self.corners();
+
+        let mut vertices: Vec<Vertex> = corners.iter().map(|corner| self.vertices[*corner as usize]).collect();
+        for triangle in vertices.chunks_exact_mut(3) {
+            let normal = normalize(cross(sub(triangle[1].position, triangle[0].position), sub(triangle[2].position, triangle[0].position)));
+            triangle.iter_mut().for_each(|vertex| vertex.normal = normal);
+        }
+
+        self.tangents = self.tangents.take().map(|tangents| corners.iter().map(|corner| tangents[*corner as usize]).collect());
+        self.vertices = vertices;
+        self.indices = None;
+        Ok(())
+    }
+
+    /// Sets each normal to the average of the triangles around the vertex, weighted by their area.
+    /// Vertices at exactly the same position count as one, so normals stay smooth across uv seams.
+    /// Fails without changing anything if the mesh doesn't pass [`MeshBuilder::validate`].
+    pub fn compute_smooth_normals(&mut self) -> Result<(), RadError> {
+        self.validate()?;
+        self.smooth_normals();
+        Ok(())
+    }
+
+    /// [`MeshBuilder::compute_smooth_normals`] for meshes already known to be valid
+    pub(super) fn smooth_normals(&mut self) {
+        let key = |vertex: &Vertex| vertex.position.map(f32::to_bits);
+        let mut normals: HashMap<[u32; 3], [f32; 3]> = HashMap::new();
+
+        for triangle in self.corners().chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|corner| &self.vertices[triangle[corner] as usize]);
+            // Left unnormalized, its length is twice the triangle's area
+            let normal = cross(sub(b.position, a.position), sub(c.position, a.position));
+
+            for vertex in [a, b, c] {
+                let sum = normals.entry(key(vertex)).or_insert([0.0; 3]);
+                *sum = add(*sum, normal);
+            }
+        }
+
+        // Vertices no triangle uses keep whatever normal they had
+        for vertex in &mut self.vertices {
+            if let Some(normal) = normals.get(&key(vertex)) {
+                vertex.normal = normalize(*normal);
+            }
+        }
+    }
+
+    /// Generates tangents pointing along increasing u, the way MikkTSpace lays them out, for normal mapping.
+    /// Uses the existing normals, so compute those first on meshes that don't have any.
+    /// Fails without changing anything if the mesh doesn't pass [`MeshBuilder::validate`].
+    pub fn generate_tangents(&mut self) -> Result<(), RadError> {
+        self.validate()?;
+        let mut tangents = vec![[0.0; 3]; self.vertices.len()];
+        let mut bitangents = vec![[0.0; 3]; self.vertices.len()];
+
+        for triangle in self.corners().chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|corner| &self.vertices[triangle[corner] as usize]);
+            let (edge1, edge2) = (sub(b.position, a.position), sub(c.position, a.position));
+            let (uv1, uv2) = ([b.uv[0] - a.uv[0], b.uv[1] - a.uv[1]], [c.uv[0] - a.uv[0], c.uv[1] - a.uv[1]]);
+
+            let determinant = uv1[0] * uv2[1] - uv2[0] * uv1[1];
+            if determinant == 0.0 {
+                continue;
+            }
+
+            // Only the sign of the determinant is kept so bigger triangles count for more, rather than ones with smaller uvs
+            let tangent = scale(sub(scale(edge1, uv2[1]), scale(edge2, uv1[1])), determinant.signum());
+            let bitangent = scale(sub(scale(edge2, uv1[0]), scale(edge1, uv2[0])), determinant.signum());
+
+            for corner in triangle {
+                tangents[*corner as usize] = add(tangents[*corner as usize], tangent);
+                bitangents[*corner as usize] = add(bitangents[*corner as usize], bitangent);
+            }
+        }
+
+        self.tangents = Some(self.vertices.iter().zip(tangents.into_iter().zip(bitangents)).map(|(vertex, (tangent, bitangent))| {
+            let normal = vertex.normal;
+            let tangent = match normalize(sub(tangent, scale(normal, dot(normal, tangent)))) {
+                tangent if dot(tangent, tangent) > 0.0 => tangent,
+                _ => perpendicular(normal),
+            };
+            let handedness = if dot(cross(normal, tangent), bitangent) < 0.0 { -1.0 } else { 1.0 };
+            [tangent[0], tangent[1], tangent[2], handedness]
+        }).collect());
+        Ok(())
+    }
+
+    /// Moves every vertex by `matrix`, column major like [`Transform::as_uniform`](crate::Transform::as_uniform).
+    /// Normals and tangents are kept at right angles to the surface, and mirroring matrices flip the winding so the mesh still faces out.
+    pub fn transform(&mut self, matrix: [[f32; 4]; 4]) {
+        let [x, y, z] = [0, 1, 2].map(|column| [matrix[column][0], matrix[column][1], matrix[column][2]]);
+        let translation = [matrix[3][0], matrix[3][1], matrix[3][2]];
+        let determinant = dot(x, cross(y, z));
+
+        // Columns of the inverse transpose, scaled by the determinant which normalizing undoes
+        let normal_matrix = [cross(y, z), cross(z, x), cross(x, y)].map(|column| scale(column, determinant.signum()));
+        let apply = |columns: &[[f32; 3]; 3], v: [f32; 3]| add(add(scale(columns[0], v[0]), scale(columns[1], v[1])), scale(columns[2], v[2]));
+
+        for vertex in &mut self.vertices {
+            vertex.position = add(apply(&[x, y, z], vertex.position), translation);
+            vertex.normal = normalize(apply(&normal_matrix, vertex.normal));
+        }
+
+        if let Some(tangents) = &mut self.tangents {
+            for tangent in tangents {
+                let [tx, ty, tz] = normalize(apply(&[x, y, z], [tangent[0], tangent[1], tangent[2]]));
+                *tangent = [tx, ty, tz, tangent[3] * determinant.signum()];
+            }
+        }
+
+        if determinant < 0.0 {
+            self.flip_winding();
+        }
+    }
+
+    /// Adds `other`'s triangles to this mesh. Tangents are only kept when both meshes have them.
+    pub fn merge(&mut self, other: &MeshBuilder) {
+        let offset = self.vertices.len() as u32;
+
+        self.indices = match (&self.indices, &other.indices) {
+            // Both drawn in order, which appending keeps
+            (None, None) => None,
+            _ => {
+                let mut corners = self.corners();
+                corners.extend(other.corners().into_iter().map(|corner| corner + offset));
+                Some(Indices::new(corners))
+            },
+        };
+
+        self.tangents = match (self.tangents.take(), &other.tangents) {
+            (Some(mut tangents), Some(other)) => {
+                tangents.extend_from_slice(other);
+                Some(tangents)
+            },
+            (None, Some(other)) if self.vertices.is_empty() => Some(other.clone()),
+            _ => None,
+        };
+
+        self.vertices.extend_from_slice(&other.vertices);
+    }
+
+    /// Reverses the order of every triangle's corners, turning which side is the front. Normals are left as they are.
+    pub fn flip_winding(&mut self) {
+        match &mut self.indices {
+            Some(Indices::U16(indices)) => indices.chunks_exact_mut(3).for_each(|triangle| triangle.swap(1, 2)),
+            Some(Indices::U32(indices)) => indices.chunks_exact_mut(3).for_each(|triangle| triangle.swap(1, 2)),
+            None => {
+                self.vertices.chunks_exact_mut(3).for_each(|triangle| triangle.swap(1, 2));
+                if let Some(tangents) = &mut self.tangents {
+                    tangents.chunks_exact_mut(3).for_each(|triangle| triangle.swap(1, 2));
+                }
+            },
+        }
+    }
+}
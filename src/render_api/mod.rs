@@ -2,4 +2,6 @@ import!(mesh);
 import!(render_api);
 import!(window);
 import!(input);
-import!(assets);
\ No newline at end of file
+import!(assets);
+import!(headless);
+import!(gltf);
\ No newline at end of file
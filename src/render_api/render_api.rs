@@ -1,21 +1,94 @@
-use bevy_ecs::component::Component;
+use std::sync::Arc;
+
+use bevy_ecs::{component::Component, event::{Event, Events}, query::QueryState, world::World};
 use winit::{event_loop::EventLoop, window::{Window, WindowBuilder}};
 
-use crate::{Mesh, MeshBuilder, Shader, ShaderSource, GLSL};
+use glium::Program;
+
+use crate::{BakedCameraInformation, Camera, HandleReserver, Mesh, MeshBuilder, OGLMesh, RadError, Shader, ShaderSource, Transform, VSync, WeakHandle, WindowResource, GLSL};
 
 
 
 
-#[derive(Clone, Copy)]
+/// Sent by the App whenever something happens to the window
+#[derive(Event, Clone, Copy, PartialEq, Debug)]
 pub enum WindowEvents
 {
-    MouseClick(u8, (f32, f32)),
-    KeyDown(char),
-    KeyUp(char)
+    Resized(u32, u32),
+    CloseRequested,
+    Focused(bool),
+}
+
+/// Sent when drawing a camera's view fails, at most once per camera per frame
+#[derive(Event, Clone, Debug)]
+pub struct DrawFailed
+{
+    pub error : Arc<RadError>,
 }
 
 pub trait RenderAPI
 {
-    fn create_mesh(&mut self, mesh_builder : MeshBuilder) -> Mesh;
-    fn create_program(&mut self, shader : &ShaderSource<GLSL>)  -> Shader;
+    fn try_create_mesh(&mut self, mesh_builder : MeshBuilder) -> Result<Mesh, RadError>;
+    fn try_create_program(&mut self, shader : &ShaderSource<GLSL>) -> Result<Shader, RadError>;
+    /// Panics with the error if the mesh can't be created, see [`RenderAPI::try_create_mesh`]
+    fn create_mesh(&mut self, mesh_builder : MeshBuilder) -> Mesh
+    {
+        self.try_create_mesh(mesh_builder).unwrap_or_else(|err| panic!("{}", err))
+    }
+    /// Panics with the compile or link log if the program can't be created, see [`RenderAPI::try_create_program`]
+    fn create_program(&mut self, shader : &ShaderSource<GLSL>) -> Shader
+    {
+        self.try_create_program(shader).unwrap_or_else(|err| panic!("{}", err))
+    }
+    /// Draws every mesh seen by the camera, a mesh that fails doesn't stop the rest from being drawn
+    fn draw(&mut self, world : &mut World, baked_camera : &BakedCameraInformation) -> Result<(), RadError>;
+    /// Lets the AssetServer reserve mesh handles before the mesh has loaded
+    fn mesh_reserver(&self) -> HandleReserver<OGLMesh>;
+    /// Creates the mesh behind a reserved handle
+    fn upload_mesh(&mut self, handle : WeakHandle<OGLMesh>, mesh_builder : MeshBuilder) -> Result<(), RadError>;
+    fn program_reserver(&self) -> HandleReserver<Program>;
+    /// Compiles the program behind a reserved handle, leaving whatever was there if compiling fails
+    fn upload_program(&mut self, handle : WeakHandle<Program>, shader : &ShaderSource<GLSL>) -> Result<(), RadError>;
+    /// Changes the swap interval, called by the App whenever [`FramePacing::vsync`](crate::FramePacing::vsync) changes.
+    /// APIs without a window have nothing to do.
+    fn set_vsync(&mut self, _vsync : VSync) -> Result<(), RadError>
+    {
+        Ok(())
+    }
+    /// Frees meshes and programs that no longer have any strong handles and sends their [`AssetEvent`](crate::AssetEvent)s, called once per frame
+    fn cleanup(&mut self, world : &mut World);
+}
+
+/// Non-send resource holding the App's RenderAPI
+pub struct RenderContext
+{
+    pub api : Box<dyn RenderAPI>,
+}
+
+pub(crate) fn render_cameras(world : &mut World)
+{
+    let Some(mut context) = world.remove_non_send_resource::<RenderContext>() else {
+        return;
+    };
+
+    let mut cameras: QueryState<(&Camera, Option<&Transform>)> = world.query();
+    let mut baked_camera_information: Vec<BakedCameraInformation> = Vec::new();
+
+    let window_size = world.resource::<WindowResource>().size();
+    for (camera, eye) in cameras.iter(world) {
+        baked_camera_information.push(camera.bake(eye, window_size, &vec![]));
+    }
+
+    for cam in baked_camera_information
+    {
+        if let Err(err) = context.api.draw(world, &cam)
+        {
+            if let Some(mut events) = world.get_resource_mut::<Events<DrawFailed>>()
+            {
+                events.send(DrawFailed { error: Arc::new(err) });
+            }
+        }
+    }
+
+    world.insert_non_send_resource(context);
 }
\ No newline at end of file
@@ -0,0 +1,2 @@
+import!(time);
+import!(timer);
\ No newline at end of file
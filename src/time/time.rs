@@ -0,0 +1,276 @@
+use std::time::Duration;
+
+use bevy_ecs::system::Resource;
+
+/// How much of each new frame's delta goes into [`Time::smoothed_delta_seconds`]
+const SMOOTHING : f64 = 0.1;
+
+/// Largest [`Time::set_time_scale`] accepted, far past anything useful but small enough that scaling a long frame can't overflow
+pub const MAX_TIME_SCALE : f64 = 1000.;
+
+/// The per-frame clock, advanced by the App at the start of every frame.
+/// `delta` and `elapsed` are scaled by `time_scale` and stop while paused,
+/// the `raw_` variants always track real time.
+#[derive(Resource, Clone, Debug)]
+pub struct Time
+{
+    delta : Duration,
+    raw_delta : Duration,
+    smoothed_delta : f64,
+    elapsed : Duration,
+    raw_elapsed : Duration,
+    frame_count : u64,
+    time_scale : f64,
+    paused : bool,
+}
+
+impl Default for Time
+{
+    fn default() -> Self {
+        Self {
+            delta: Duration::ZERO,
+            raw_delta: Duration::ZERO,
+            smoothed_delta: 0.,
+            elapsed: Duration::ZERO,
+            raw_elapsed: Duration::ZERO,
+            frame_count: 0,
+            time_scale: 1.,
+            paused: false,
+        }
+    }
+}
+
+impl Time
+{
+    pub fn delta(&self) -> Duration
+    {
+        self.delta
+    }
+
+    pub fn delta_seconds(&self) -> f32
+    {
+        self.delta.as_secs_f32()
+    }
+
+    pub fn delta_seconds_f64(&self) -> f64
+    {
+        self.delta.as_secs_f64()
+    }
+
+    /// Exponential moving average of the scaled delta, steadier than `delta_seconds` for display or camera smoothing
+    pub fn smoothed_delta_seconds(&self) -> f64
+    {
+        self.smoothed_delta
+    }
+
+    pub fn raw_delta(&self) -> Duration
+    {
+        self.raw_delta
+    }
+
+    pub fn elapsed(&self) -> Duration
+    {
+        self.elapsed
+    }
+
+    pub fn elapsed_seconds(&self) -> f32
+    {
+        self.elapsed.as_secs_f32()
+    }
+
+    pub fn elapsed_seconds_f64(&self) -> f64
+    {
+        self.elapsed.as_secs_f64()
+    }
+
+    pub fn raw_elapsed(&self) -> Duration
+    {
+        self.raw_elapsed
+    }
+
+    /// Number of frames since the App started
+    pub fn frame_count(&self) -> u64
+    {
+        self.frame_count
+    }
+
+    pub fn time_scale(&self) -> f64
+    {
+        self.time_scale
+    }
+
+    /// Clamped between zero and [`MAX_TIME_SCALE`], NaN is ignored
+    pub fn set_time_scale(&mut self, time_scale : f64)
+    {
+        if !time_scale.is_nan()
+        {
+            self.time_scale = time_scale.clamp(0., MAX_TIME_SCALE);
+        }
+    }
+
+    pub fn pause(&mut self)
+    {
+        self.paused = true;
+    }
+
+    pub fn unpause(&mut self)
+    {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool
+    {
+        self.paused
+    }
+
+    /// Scales a real duration the same way `delta` is
+    pub fn scale(&self, raw : Duration) -> Duration
+    {
+        if self.paused
+        {
+            Duration::ZERO
+        } else {
+            raw.mul_f64(self.time_scale)
+        }
+    }
+
+    pub(crate) fn advance(&mut self, raw_delta : Duration)
+    {
+        self.raw_delta = raw_delta;
+        self.raw_elapsed += raw_delta;
+
+        self.delta = self.scale(raw_delta);
+        self.elapsed += self.delta;
+
+        self.smoothed_delta = if self.frame_count == 0
+        {
+            self.delta.as_secs_f64()
+        } else {
+            self.smoothed_delta + (self.delta.as_secs_f64() - self.smoothed_delta) * SMOOTHING
+        };
+
+        self.frame_count += 1;
+    }
+}
+
+/// Seconds since the last frame, or the fixed step while `Update` is running.
+/// Kept updated by the App alongside [`Time`] and [`FixedTime`] for code written before they existed.
+#[deprecated(note = "use `Time` for the frame's delta or `FixedTime` inside `Update`")]
+#[derive(Default, Clone, Copy)]
+pub struct DeltaTime
+{
+    pub delta_time : f64,
+}
+
+// Written out as the derive would trip the deprecation warning
+#[allow(deprecated)]
+impl Resource for DeltaTime {}
+
+/// The clock `Update` runs on.
+/// `Update` is stepped every [`FixedTime::step`] of real time, catching up at most `max_steps_per_frame`
+/// times per frame before the backlog is dropped. `delta` is `step` scaled by [`Time`],
+/// so gameplay using it slows down or stops with the rest of the game while `Update` keeps running.
+#[derive(Resource, Clone, Debug)]
+pub struct FixedTime
+{
+    step : Duration,
+    pub max_steps_per_frame : u32,
+
+    delta : Duration,
+    elapsed : Duration,
+    tick_count : u64,
+    overstep : f64,
+}
+
+impl Default for FixedTime
+{
+    fn default() -> Self {
+        Self::from_hz(60.)
+    }
+}
+
+impl FixedTime
+{
+    /// Panics if `step` is zero
+    pub fn new(step : Duration) -> Self
+    {
+        assert!(!step.is_zero(), "FixedTime needs a step above zero");
+        Self {
+            step,
+            max_steps_per_frame: 5,
+            delta: Duration::ZERO,
+            elapsed: Duration::ZERO,
+            tick_count: 0,
+            overstep: 0.,
+        }
+    }
+
+    /// Panics unless `hz` is finite and above zero
+    pub fn from_hz(hz : f64) -> Self
+    {
+        assert!(hz.is_finite() && hz > 0., "FixedTime needs a positive, finite rate, got {} Hz", hz);
+        Self::new(Duration::from_secs_f64(1. / hz))
+    }
+
+    /// Real time between two runs of `Update`
+    pub fn step(&self) -> Duration
+    {
+        self.step
+    }
+
+    /// Panics if `step` is zero
+    pub fn set_step(&mut self, step : Duration)
+    {
+        assert!(!step.is_zero(), "FixedTime needs a step above zero");
+        self.step = step;
+    }
+
+    pub fn delta(&self) -> Duration
+    {
+        self.delta
+    }
+
+    pub fn delta_seconds(&self) -> f32
+    {
+        self.delta.as_secs_f32()
+    }
+
+    pub fn delta_seconds_f64(&self) -> f64
+    {
+        self.delta.as_secs_f64()
+    }
+
+    pub fn elapsed(&self) -> Duration
+    {
+        self.elapsed
+    }
+
+    pub fn elapsed_seconds(&self) -> f32
+    {
+        self.elapsed.as_secs_f32()
+    }
+
+    /// Number of times `Update` has run
+    pub fn tick_count(&self) -> u64
+    {
+        self.tick_count
+    }
+
+    /// How far into the next step the App is, from 0 to 1. Useful for interpolating in `Draw`.
+    pub fn overstep_fraction(&self) -> f64
+    {
+        self.overstep
+    }
+
+    pub(crate) fn tick(&mut self, delta : Duration)
+    {
+        self.delta = delta;
+        self.elapsed += delta;
+        self.tick_count += 1;
+    }
+
+    pub(crate) fn set_overstep(&mut self, overstep : f64)
+    {
+        self.overstep = overstep;
+    }
+}
@@ -0,0 +1,244 @@
+use std::time::Duration;
+
+use bevy_ecs::{component::Component, system::{Query, Res, Resource}};
+
+use crate::FixedTime;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TimerMode
+{
+    /// Finishes once and stays finished until reset
+    #[default]
+    Once,
+    /// Wraps around every time it finishes
+    Repeating,
+}
+
+/// Counts down a duration.
+/// As a component it's ticked by the [`TimePlugin`](crate::TimePlugin) before every `Update`,
+/// as a resource or inside your own components call [`Timer::tick`] yourself.
+#[derive(Component, Resource, Clone, Debug, Default)]
+pub struct Timer
+{
+    duration : Duration,
+    elapsed : Duration,
+    mode : TimerMode,
+    paused : bool,
+    finished : bool,
+    times_finished_this_tick : u32,
+}
+
+impl Timer
+{
+    pub fn new(duration : Duration, mode : TimerMode) -> Self
+    {
+        Self { duration, mode, ..Default::default() }
+    }
+
+    pub fn from_seconds(seconds : f32, mode : TimerMode) -> Self
+    {
+        Self::new(Duration::from_secs_f32(seconds), mode)
+    }
+
+    pub fn tick(&mut self, delta : Duration) -> &Self
+    {
+        if self.paused
+        {
+            self.times_finished_this_tick = 0;
+            return self;
+        }
+
+        if self.mode == TimerMode::Once && self.finished
+        {
+            self.times_finished_this_tick = 0;
+            return self;
+        }
+
+        self.elapsed += delta;
+
+        if self.elapsed >= self.duration
+        {
+            self.finished = true;
+
+            if self.mode == TimerMode::Repeating && !self.duration.is_zero()
+            {
+                self.times_finished_this_tick = (self.elapsed.as_nanos() / self.duration.as_nanos()) as u32;
+                self.elapsed = Duration::from_nanos((self.elapsed.as_nanos() % self.duration.as_nanos()) as u64);
+            } else {
+                self.times_finished_this_tick = 1;
+                self.elapsed = self.duration;
+            }
+        } else {
+            self.times_finished_this_tick = 0;
+            if self.mode == TimerMode::Repeating
+            {
+                self.finished = false;
+            }
+        }
+
+        self
+    }
+
+    /// True once the timer has reached its duration, for repeating timers only on the tick it wrapped
+    pub fn finished(&self) -> bool
+    {
+        self.finished
+    }
+
+    /// True only on the tick the timer finished
+    pub fn just_finished(&self) -> bool
+    {
+        self.times_finished_this_tick > 0
+    }
+
+    /// A repeating timer with a short duration can finish several times in one tick
+    pub fn times_finished_this_tick(&self) -> u32
+    {
+        self.times_finished_this_tick
+    }
+
+    pub fn elapsed(&self) -> Duration
+    {
+        self.elapsed
+    }
+
+    pub fn remaining(&self) -> Duration
+    {
+        self.duration.saturating_sub(self.elapsed)
+    }
+
+    /// Elapsed time as a fraction of the duration, from 0 to 1
+    pub fn fraction(&self) -> f32
+    {
+        if self.duration.is_zero()
+        {
+            1.
+        } else {
+            self.elapsed.as_secs_f32() / self.duration.as_secs_f32()
+        }
+    }
+
+    pub fn duration(&self) -> Duration
+    {
+        self.duration
+    }
+
+    pub fn set_duration(&mut self, duration : Duration)
+    {
+        self.duration = duration;
+    }
+
+    pub fn mode(&self) -> TimerMode
+    {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode : TimerMode)
+    {
+        self.mode = mode;
+    }
+
+    pub fn pause(&mut self)
+    {
+        self.paused = true;
+    }
+
+    pub fn unpause(&mut self)
+    {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool
+    {
+        self.paused
+    }
+
+    pub fn reset(&mut self)
+    {
+        self.elapsed = Duration::ZERO;
+        self.finished = false;
+        self.times_finished_this_tick = 0;
+    }
+}
+
+/// Counts up from zero.
+/// As a component it's ticked by the [`TimePlugin`](crate::TimePlugin) before every `Update`.
+#[derive(Component, Resource, Clone, Debug, Default)]
+pub struct Stopwatch
+{
+    elapsed : Duration,
+    paused : bool,
+}
+
+impl Stopwatch
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    pub fn tick(&mut self, delta : Duration) -> &Self
+    {
+        if !self.paused
+        {
+            self.elapsed += delta;
+        }
+        self
+    }
+
+    pub fn elapsed(&self) -> Duration
+    {
+        self.elapsed
+    }
+
+    pub fn elapsed_seconds(&self) -> f32
+    {
+        self.elapsed.as_secs_f32()
+    }
+
+    pub fn pause(&mut self)
+    {
+        self.paused = true;
+    }
+
+    pub fn unpause(&mut self)
+    {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool
+    {
+        self.paused
+    }
+
+    pub fn reset(&mut self)
+    {
+        self.elapsed = Duration::ZERO;
+    }
+}
+
+pub(crate) fn tick_timers(mut timers : Query<&mut Timer>, mut stopwatches : Query<&mut Stopwatch>, time : Res<FixedTime>)
+{
+    for mut timer in timers.iter_mut()
+    {
+        timer.tick(time.delta());
+    }
+
+    for mut stopwatch in stopwatches.iter_mut()
+    {
+        stopwatch.tick(time.delta());
+    }
+}
+
+/// Run condition that's true once every `duration` of [`FixedTime`], for systems in `Update`
+/// ```ignore
+/// app.add_systems(Update, spawn_enemy.run_if(on_timer(Duration::from_secs(5))));
+/// ```
+pub fn on_timer(duration : Duration) -> impl FnMut(Res<FixedTime>) -> bool + Clone
+{
+    let mut timer = Timer::new(duration, TimerMode::Repeating);
+    move |time : Res<FixedTime>| {
+        timer.tick(time.delta());
+        timer.just_finished()
+    }
+}
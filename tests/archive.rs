@@ -0,0 +1,110 @@
+use std::path::PathBuf;
+
+use rad::*;
+
+/// A file in the temp dir, removed when dropped
+struct TempFile(PathBuf);
+
+impl TempFile
+{
+    fn new(name : &str, bytes : &[u8]) -> Self
+    {
+        let path = std::env::temp_dir().join(format!("rad-{}-{}.pack", name, std::process::id()));
+        std::fs::write(&path, bytes).unwrap();
+        Self(path)
+    }
+}
+
+impl Drop for TempFile
+{
+    fn drop(&mut self)
+    {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn pack(builder : ArchiveBuilder) -> Vec<u8>
+{
+    let mut bytes = vec![];
+    builder.write_to(&mut bytes).unwrap();
+    bytes
+}
+
+fn two_files() -> ArchiveBuilder
+{
+    ArchiveBuilder::new()
+        .add_file("meshes/triangle.obj", b"v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n".to_vec())
+        .add_file("repeated.txt", vec![b'a'; 4096])
+}
+
+#[test]
+fn packed_files_read_back_the_same()
+{
+    for compress in [true, false]
+    {
+        let file = TempFile::new(&format!("round-trip-{}", compress), &pack(two_files().compress(compress)));
+        let archive = Archive::open(&file.0).unwrap();
+
+        assert_eq!(archive.entries().count(), 2);
+        assert_eq!(archive.read("meshes/triangle.obj").unwrap(), b"v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n");
+        assert_eq!(archive.read("repeated.txt").unwrap(), vec![b'a'; 4096]);
+        assert_eq!(archive.entries().find(|(path, _)| *path == "repeated.txt").unwrap().1.compressed, compress);
+        assert!(archive.read("missing.txt").is_err());
+    }
+}
+
+#[test]
+fn corrupt_archives_are_rejected()
+{
+    let bytes = pack(two_files());
+
+    let mut magic = bytes.clone();
+    magic[0] = b'X';
+
+    // Entry count, right after the magic and version
+    let mut count = bytes.clone();
+    count[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    // The first entry's stored length, after its path length, path and offset
+    let stored_len = 16 + 2 + "meshes/triangle.obj".len() + 8;
+    let mut past_end = bytes.clone();
+    past_end[stored_len..stored_len + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+
+    let truncated = bytes[..bytes.len() - 1].to_vec();
+
+    for (name, bytes) in [("magic", magic), ("count", count), ("past-end", past_end), ("truncated", truncated)]
+    {
+        let file = TempFile::new(&format!("corrupt-{}", name), &bytes);
+        assert_eq!(Archive::open(&file.0).err().map(|err| err.kind()), Some(std::io::ErrorKind::InvalidData), "{}", name);
+    }
+
+    // Flipped file data passes the index checks but not the content hash
+    let mut data = bytes.clone();
+    let last = data.len() - 1;
+    data[last] ^= 0xff;
+    let file = TempFile::new("corrupt-data", &data);
+    assert!(Archive::open(&file.0).unwrap().read("repeated.txt").is_err());
+}
+
+#[test]
+fn a_missing_archive_fails_loads_instead_of_panicking()
+{
+    let mut app = App::headless();
+    app.add_plugins(DefaultPlugins.build().set(AssetPlugin { archive: Some(PathBuf::from("missing.pack")), ..Default::default() }));
+
+    let handle : Handle<OGLMesh> = app.world.resource::<AssetServer>().load("triangle.obj");
+    for _ in 0..500
+    {
+        app.step(1);
+        if app.world.resource::<AssetServer>().load_state(&handle) != LoadState::Loading
+        {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+
+    assert!(matches!(app.world.resource::<AssetServer>().load_state(&handle), LoadState::Failed(_)));
+    let failed : Vec<AssetLoadFailed> = app.world.resource_mut::<Events<AssetLoadFailed>>().drain().collect();
+    assert!(matches!(*failed[0].error, RadError::Io(_)));
+    assert!(failed[0].error.to_string().contains("missing.pack"), "{}", failed[0].error);
+}
@@ -0,0 +1,384 @@
+use std::{error::Error, fs::File, path::{Path, PathBuf}, sync::{atomic::{AtomicUsize, Ordering}, Arc, Mutex}, thread::sleep, time::{Duration, SystemTime}};
+
+use rad::*;
+
+const TRIANGLE : &str = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n";
+
+/// A fresh folder in the temp dir, removed when dropped
+struct TempDir(PathBuf);
+
+impl TempDir
+{
+    fn new(name : &str) -> Self
+    {
+        let path = std::env::temp_dir().join(format!("rad-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    fn write(&self, file : &str, contents : &str)
+    {
+        std::fs::write(self.0.join(file), contents).unwrap();
+    }
+}
+
+impl Drop for TempDir
+{
+    fn drop(&mut self)
+    {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn app(root : &Path) -> App
+{
+    let mut app = App::headless();
+    app.add_plugins(DefaultPlugins.build().set(AssetPlugin { root: root.to_path_buf(), ..Default::default() }));
+    app
+}
+
+fn load(app : &App, path : &str) -> Handle<OGLMesh>
+{
+    app.world.resource::<AssetServer>().load(path)
+}
+
+fn state(app : &App, handle : impl Into<WeakHandle<OGLMesh>>) -> LoadState
+{
+    app.world.resource::<AssetServer>().load_state(handle)
+}
+
+/// Takes the AssetLoadFailed events sent so far
+fn failures(app : &mut App) -> Vec<AssetLoadFailed>
+{
+    app.world.resource_mut::<Events<AssetLoadFailed>>().drain().collect()
+}
+
+/// Steps the App until the handle stops loading
+fn wait_for<T : 'static>(app : &mut App, handle : &Handle<T>) -> LoadState
+{
+    for _ in 0..500
+    {
+        app.step(1);
+        if app.world.resource::<AssetServer>().load_state(handle) != LoadState::Loading
+        {
+            break;
+        }
+        sleep(Duration::from_millis(5));
+    }
+    app.world.resource::<AssetServer>().load_state(handle)
+}
+
+#[test]
+fn loading_a_live_path_again_gives_the_same_handle()
+{
+    let dir = TempDir::new("same-handle");
+    dir.write("triangle.obj", TRIANGLE);
+    let mut app = app(&dir.0);
+
+    let first = load(&app, "triangle.obj");
+    assert_eq!(load(&app, "triangle.obj"), first);
+    assert_eq!(wait_for(&mut app, &first), LoadState::Loaded);
+
+    let missing = load(&app, "missing.obj");
+    assert!(matches!(wait_for(&mut app, &missing), LoadState::Failed(_)));
+}
+
+#[test]
+fn freed_assets_are_forgotten()
+{
+    let dir = TempDir::new("forgotten");
+    dir.write("triangle.obj", TRIANGLE);
+    let mut app = app(&dir.0);
+
+    let handle = load(&app, "triangle.obj");
+    assert_eq!(wait_for(&mut app, &handle), LoadState::Loaded);
+
+    let weak = handle.downgrade();
+    drop(handle);
+    // Freed at the end of this frame, forgotten by the AssetServer at the start of the next
+    app.step(2);
+    assert_eq!(state(&app, weak), LoadState::NotLoaded);
+
+    // Loading it again starts from scratch
+    let handle = load(&app, "triangle.obj");
+    assert_ne!(handle.downgrade(), weak);
+    assert_eq!(wait_for(&mut app, &handle), LoadState::Loaded);
+}
+
+#[test]
+fn failed_loads_send_an_event()
+{
+    let dir = TempDir::new("failed-load");
+    let mut app = app(&dir.0);
+
+    let missing = load(&app, "missing.obj");
+    assert!(matches!(wait_for(&mut app, &missing), LoadState::Failed(_)));
+
+    let failed = failures(&mut app);
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].path, "missing.obj");
+    assert!(!failed[0].reload);
+    assert!(matches!(&*failed[0].error, RadError::Io(err) if err.kind() == std::io::ErrorKind::NotFound));
+    assert_eq!(state(&app, &missing), LoadState::Failed(failed[0].error.clone()));
+
+    // Nothing loads meshes from .txt files, that's known without going to the loading thread
+    let unknown = load(&app, "mesh.txt");
+    assert!(matches!(state(&app, &unknown), LoadState::Failed(err) if matches!(*err, RadError::NoLoader(_))));
+
+    app.step(1);
+    let failed = failures(&mut app);
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].path, "mesh.txt");
+    assert_eq!(state(&app, &unknown), LoadState::Failed(failed[0].error.clone()));
+}
+
+#[test]
+fn paths_outside_the_root_are_refused()
+{
+    let dir = TempDir::new("outside-root");
+    std::fs::create_dir_all(dir.0.join("root")).unwrap();
+    dir.write("triangle.obj", TRIANGLE);
+    let mut app = app(&dir.0.join("root"));
+
+    let absolute = dir.0.join("triangle.obj");
+    for path in ["../triangle.obj", "./../triangle.obj", absolute.to_str().unwrap()]
+    {
+        let handle = load(&app, path);
+        assert!(matches!(wait_for(&mut app, &handle), LoadState::Failed(err) if matches!(&*err, RadError::Io(err) if err.kind() == std::io::ErrorKind::InvalidInput)), "{}", path);
+    }
+}
+
+#[test]
+fn failed_reloads_keep_the_old_asset_and_send_an_event()
+{
+    let dir = TempDir::new("failed-reload");
+    dir.write("triangle.obj", TRIANGLE);
+    let mut app = App::headless();
+    app.add_plugins(DefaultPlugins.build().set(AssetPlugin { root: dir.0.clone(), watch_for_changes: true, ..Default::default() }));
+
+    let handle = load(&app, "triangle.obj");
+    assert_eq!(wait_for(&mut app, &handle), LoadState::Loaded);
+    assert!(failures(&mut app).is_empty());
+
+    // Not UTF-8, so the OBJ loader rejects it. The timestamp is moved on in case the file system's is coarse
+    let path = dir.0.join("triangle.obj");
+    std::fs::write(&path, [0xff, 0xfe]).unwrap();
+    File::options().write(true).open(&path).unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(2)).unwrap();
+
+    let mut failed = vec![];
+    for _ in 0..500
+    {
+        app.step(1);
+        failed.extend(failures(&mut app));
+        if !failed.is_empty()
+        {
+            break;
+        }
+        sleep(Duration::from_millis(5));
+    }
+
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].path, "triangle.obj");
+    assert!(failed[0].reload);
+    assert_eq!(state(&app, &handle), LoadState::Loaded);
+}
+
+struct Note(String);
+
+impl CpuAsset for Note {}
+
+/// Counts how often notes are loaded from source and from the cache
+#[derive(Clone, Default)]
+struct NoteLoader
+{
+    settings : u64,
+    loads : Arc<AtomicUsize>,
+    cached_loads : Arc<AtomicUsize>,
+}
+
+impl AssetLoader for NoteLoader
+{
+    type Asset = Note;
+
+    fn extensions(&self) -> &[&str]
+    {
+        &["note"]
+    }
+
+    fn load(&self, bytes : Vec<u8>, _context : &mut LoadContext) -> Result<Note, Box<dyn Error + Send + Sync>>
+    {
+        self.loads.fetch_add(1, Ordering::SeqCst);
+        Ok(Note(String::from_utf8(bytes)?))
+    }
+
+    fn settings_hash(&self) -> u64
+    {
+        self.settings
+    }
+
+    fn processed_format(&self) -> Option<&str>
+    {
+        Some("test.note.1")
+    }
+
+    fn process(&self, loaded : &Note) -> Option<Vec<u8>>
+    {
+        Some(loaded.0.as_bytes().to_vec())
+    }
+
+    fn load_processed(&self, bytes : Vec<u8>) -> Result<Note, Box<dyn Error + Send + Sync>>
+    {
+        self.cached_loads.fetch_add(1, Ordering::SeqCst);
+        Ok(Note(String::from_utf8(bytes)?))
+    }
+}
+
+/// Loads `note.note` in a fresh App, returning what it read and how many times it loaded from source and from the cache
+fn load_note(dir : &TempDir, loader : &NoteLoader) -> (String, usize, usize)
+{
+    loader.loads.store(0, Ordering::SeqCst);
+    loader.cached_loads.store(0, Ordering::SeqCst);
+
+    let mut app = App::headless();
+    app.add_plugins(DefaultPlugins.build().set(AssetPlugin { root: dir.0.join("source"), processed: Some(dir.0.join("processed")), ..Default::default() }));
+    app.init_asset::<Note>();
+    app.add_asset_loader(loader.clone());
+
+    let handle : Handle<Note> = app.world.resource::<AssetServer>().load("note.note");
+    for _ in 0..500
+    {
+        app.step(1);
+        if app.world.resource::<AssetServer>().load_state(&handle) != LoadState::Loading
+        {
+            break;
+        }
+        sleep(Duration::from_millis(5));
+    }
+
+    let text = app.world.resource::<Assets<Note>>().get_asset(&handle).map(|note| note.0.clone()).unwrap_or_default();
+    (text, loader.loads.load(Ordering::SeqCst), loader.cached_loads.load(Ordering::SeqCst))
+}
+
+#[test]
+fn processed_assets_are_reused_until_the_source_or_settings_change()
+{
+    let dir = TempDir::new("processed");
+    std::fs::create_dir_all(dir.0.join("source")).unwrap();
+    dir.write("source/note.note", "first");
+    let loader = NoteLoader::default();
+
+    assert_eq!(load_note(&dir, &loader), ("first".to_string(), 1, 0));
+    assert_eq!(load_note(&dir, &loader), ("first".to_string(), 0, 1));
+
+    dir.write("source/note.note", "second");
+    assert_eq!(load_note(&dir, &loader), ("second".to_string(), 1, 0));
+    assert_eq!(load_note(&dir, &loader), ("second".to_string(), 0, 1));
+
+    let loader = NoteLoader { settings: 1, ..Default::default() };
+    assert_eq!(load_note(&dir, &loader), ("second".to_string(), 1, 0));
+    assert_eq!(load_note(&dir, &loader), ("second".to_string(), 0, 1));
+}
+
+#[test]
+fn failed_cache_writes_send_an_event()
+{
+    let dir = TempDir::new("processed-unwritable");
+    dir.write("triangle.obj", TRIANGLE);
+    // A file where the cache folder should be
+    dir.write("processed", "");
+
+    let mut app = App::headless();
+    app.add_plugins(DefaultPlugins.build().set(AssetPlugin { root: dir.0.clone(), processed: Some(dir.0.join("processed")), ..Default::default() }));
+
+    let handle = load(&app, "triangle.obj");
+    assert_eq!(wait_for(&mut app, &handle), LoadState::Loaded);
+
+    let failed : Vec<ProcessedAssetWriteFailed> = app.world.resource_mut::<Events<ProcessedAssetWriteFailed>>().drain().collect();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].path, "triangle.obj");
+    assert!(matches!(*failed[0].error, RadError::Io(_)));
+}
+
+/// Loads the whole text of a `.book` as a note, with a sub-asset for each line labeled `line0`, `line1`, ...
+#[derive(Clone, Default)]
+struct BookLoader
+{
+    loads : Arc<AtomicUsize>,
+    /// Loads wait while a test holds this
+    gate : Arc<Mutex<()>>,
+}
+
+impl AssetLoader for BookLoader
+{
+    type Asset = Note;
+
+    fn extensions(&self) -> &[&str]
+    {
+        &["book"]
+    }
+
+    fn load(&self, bytes : Vec<u8>, context : &mut LoadContext) -> Result<Note, Box<dyn Error + Send + Sync>>
+    {
+        drop(self.gate.lock().unwrap());
+        self.loads.fetch_add(1, Ordering::SeqCst);
+        let text = String::from_utf8(bytes)?;
+        for (number, line) in text.lines().enumerate()
+        {
+            context.add_labeled_asset::<Note>(&format!("line{}", number), Note(line.to_string()));
+        }
+        Ok(Note(text))
+    }
+}
+
+fn book_app(dir : &TempDir, loader : &BookLoader) -> App
+{
+    let mut app = app(&dir.0);
+    app.init_asset::<Note>();
+    app.add_asset_loader(loader.clone());
+    app
+}
+
+#[test]
+fn sub_assets_asked_for_while_their_file_loads_come_out_of_that_load()
+{
+    let dir = TempDir::new("book-loading");
+    dir.write("book.book", "first\nsecond");
+    let loader = BookLoader::default();
+    let mut app = book_app(&dir, &loader);
+
+    let gate = loader.gate.lock().unwrap();
+    let server = app.world.resource::<AssetServer>();
+    let book : Handle<Note> = server.load("book.book");
+    let second : Handle<Note> = server.load("book.book#line1");
+    let first : Handle<Note> = server.load("book.book#line0");
+    drop(gate);
+
+    for handle in [&book, &second, &first]
+    {
+        assert_eq!(wait_for(&mut app, handle), LoadState::Loaded);
+    }
+    assert_eq!(loader.loads.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn labeled_sub_assets_load_on_their_own()
+{
+    let dir = TempDir::new("book-labels");
+    dir.write("book.book", "first\nsecond");
+    let mut app = book_app(&dir, &BookLoader::default());
+
+    let server = app.world.resource::<AssetServer>();
+    let second : Handle<Note> = server.load("book.book#line1");
+    let missing : Handle<Note> = server.load("book.book#line2");
+
+    assert_eq!(wait_for(&mut app, &second), LoadState::Loaded);
+    assert_eq!(app.world.resource::<Assets<Note>>().get_asset(&second).map(|note| note.0.as_str()), Some("second"));
+    assert!(matches!(wait_for(&mut app, &missing), LoadState::Failed(err) if err.to_string().contains("line2")));
+
+    // The file's own asset wasn't asked for, so it loads separately
+    let book : Handle<Note> = app.world.resource::<AssetServer>().load("book.book");
+    assert_eq!(wait_for(&mut app, &book), LoadState::Loaded);
+    assert_eq!(app.world.resource::<Assets<Note>>().get_asset(&book).map(|note| note.0.as_str()), Some("first\nsecond"));
+}
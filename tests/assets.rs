@@ -0,0 +1,115 @@
+use rad::*;
+
+struct Note(String);
+
+impl CpuAsset for Note {}
+
+#[test]
+fn assets_are_freed_once_the_last_handle_drops()
+{
+    let mut assets = Assets::new();
+    let handle = assets.add_asset(5u32);
+    let weak = handle.downgrade();
+    let clone = handle.clone();
+
+    drop(handle);
+    assert_eq!(assets.cleanup(), 0);
+    assert_eq!(assets.get_asset(weak), Some(&5));
+
+    // Upgrading a weak handle keeps the asset alive too
+    let upgraded = assets.upgrade(weak).unwrap();
+    drop(clone);
+    assert_eq!(assets.cleanup(), 0);
+
+    drop(upgraded);
+    assert_eq!(assets.cleanup(), 1);
+    assert!(!assets.contains(weak));
+    assert!(assets.upgrade(weak).is_none());
+    assert!(assets.is_empty());
+}
+
+#[test]
+fn freed_slots_are_reused_without_reviving_old_handles()
+{
+    let mut assets = Assets::new();
+    let old = assets.add_asset("old").downgrade();
+    assets.cleanup();
+
+    let new = assets.add_asset("new");
+    assert_eq!(assets.len(), 1);
+    assert_eq!(assets.get_asset(&new), Some(&"new"));
+    assert_eq!(assets.get_asset(old), None);
+    assert!(assets.insert(old, "stale").is_err());
+    assert!(assets.remove_asset(old).is_err());
+}
+
+#[test]
+fn reserved_handles_can_be_filled_later()
+{
+    let mut assets = Assets::new();
+    let reserved = assets.reserver().reserve();
+    assert!(!assets.contains(&reserved));
+
+    assert_eq!(assets.insert(&reserved, 1u8), Ok(None));
+    assert_eq!(assets.insert(&reserved, 2u8), Ok(Some(1)));
+    assert_eq!(assets.get_asset(&reserved), Some(&2));
+
+    // A reservation dropped before it was filled frees nothing
+    drop(assets.reserver().reserve());
+    assert_eq!(assets.cleanup(), 0);
+}
+
+#[test]
+fn untyped_handles_only_convert_back_to_their_own_type()
+{
+    let mut assets = Assets::new();
+    let handle = assets.add_asset(1u32);
+
+    let untyped = handle.clone().untyped();
+    assert!(untyped.is::<u32>());
+    assert!(untyped.clone().typed::<f32>().is_none());
+    assert_eq!(untyped.typed::<u32>(), Some(handle));
+}
+
+#[test]
+fn changes_are_sent_as_asset_events()
+{
+    #[derive(Resource, Default)]
+    struct Sent(Vec<AssetEvent<Note>>);
+
+    let mut app = App::headless();
+    app.add_plugins(DefaultPlugins);
+    app.init_asset::<Note>();
+    app.world.init_resource::<Sent>();
+    app.add_systems(Update, |mut events : EventReader<AssetEvent<Note>>, mut sent : ResMut<Sent>| sent.0.extend(events.read().copied()));
+
+    let handle = app.world.resource_mut::<Assets<Note>>().add_asset(Note("text".to_string()));
+    let weak = handle.downgrade();
+    app.step(1);
+    assert_eq!(app.world.resource::<Assets<Note>>().get_asset(weak).map(|note| note.0.as_str()), Some("text"));
+
+    drop(handle);
+    app.step(1);
+    assert_eq!(app.world.resource::<Sent>().0, [AssetEvent::Added { handle: weak }, AssetEvent::Removed { handle: weak }]);
+}
+
+#[test]
+fn only_marked_changes_are_sent_as_modified()
+{
+    let mut assets = Assets::<Note>::new();
+    assets.enable_events();
+    let handle = assets.add_asset(Note("text".to_string()));
+    let weak = handle.downgrade();
+    assets.drain_events();
+
+    assets.get_asset_mut(&handle).unwrap().0.push('!');
+    assets.iter_mut().for_each(|(_, note)| note.0.push('!'));
+    assert_eq!(assets.drain_events().count(), 0);
+
+    assert!(assets.mark_modified(&handle));
+    assert_eq!(assets.drain_events().collect::<Vec<_>>(), [AssetEvent::Modified { handle: weak }]);
+    assert_eq!(assets.get_asset(weak).map(|note| note.0.as_str()), Some("text!!"));
+
+    assets.remove_asset(weak).unwrap();
+    assert!(!assets.mark_modified(weak));
+}
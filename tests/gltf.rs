@@ -0,0 +1,288 @@
+use std::f32::consts::FRAC_PI_2;
+
+use rad::{App, Children, Colour, DefaultMaterial, Entity, Gltf, Indices, Mesh, MeshBuilder, Name, Parent, RadError, Transform};
+
+const RES : &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/res");
+
+fn open(file : &str) -> Gltf<MeshBuilder>
+{
+    Gltf::open(format!("{}/{}", RES, file)).unwrap_or_else(|err| panic!("{}: {}", file, err))
+}
+
+fn close(a : f32, b : f32) -> bool
+{
+    (a - b).abs() < 1e-4
+}
+
+/// A minimal glTF around `extra` members, with a one triangle buffer in a data URI
+fn minimal(extra : &str) -> String
+{
+    // Positions (0,0,0), (1,0,0), (0,1,0) then u8 indices 0, 1, 2, 5
+    let buffer = "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAAAAECBQ==";
+    format!(r#"{{
+        "asset": {{ "version": "2.0" }},
+        "buffers": [{{ "byteLength": 40, "uri": "{}" }}],
+        "bufferViews": [{{ "buffer": 0, "byteLength": 36 }}, {{ "buffer": 0, "byteOffset": 36, "byteLength": 4 }}],
+        "accessors": [
+            {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" }},
+            {{ "bufferView": 1, "componentType": 5121, "count": 3, "type": "SCALAR" }},
+            {{ "bufferView": 1, "byteOffset": 1, "componentType": 5121, "count": 3, "type": "SCALAR" }}
+        ]
+        {}
+    }}"#, buffer, extra)
+}
+
+fn parse(text : &str) -> Result<Gltf<MeshBuilder>, RadError>
+{
+    Gltf::from_slice(text.as_bytes(), |uri| Err(RadError::Parse(format!("No file {}", uri))))
+}
+
+#[test]
+fn embedded_external_and_binary_files_read_the_same()
+{
+    let embedded = open("embedded.gltf");
+
+    for other in [open("external.gltf"), open("binary.glb")]
+    {
+        assert_eq!(other.materials, embedded.materials);
+        assert_eq!(other.nodes, embedded.nodes);
+        assert_eq!(other.scenes, embedded.scenes);
+        assert_eq!(other.default_scene, embedded.default_scene);
+
+        for (mesh, expected) in other.meshes.iter().zip(&embedded.meshes)
+        {
+            assert_eq!(mesh.name, expected.name);
+            for (primitive, expected) in mesh.primitives.iter().zip(&expected.primitives)
+            {
+                assert_eq!(primitive.material, expected.material);
+                assert_eq!(primitive.mesh.to_bytes().unwrap(), expected.mesh.to_bytes().unwrap());
+            }
+        }
+    }
+}
+
+#[test]
+fn meshes_keep_every_primitive()
+{
+    let gltf = open("embedded.gltf");
+    assert_eq!(gltf.meshes.len(), 2);
+    assert_eq!(gltf.meshes[0].name.as_deref(), Some("Pieces"));
+    assert_eq!(gltf.meshes[0].primitives.len(), 2);
+
+    let triangle = &gltf.meshes[0].primitives[0];
+    assert_eq!(triangle.material, Some(0));
+    assert_eq!(triangle.mesh.indices, Some(Indices::U16(vec![0, 1, 2])));
+    assert_eq!(triangle.mesh.vertices[1].position, [1.0, 0.0, 0.0]);
+    assert_eq!(triangle.mesh.vertices[1].normal, [0.0, 0.0, 1.0]);
+    // v is flipped to match OBJ files
+    assert_eq!(triangle.mesh.vertices[0].uv, [0.0, 1.0]);
+    assert_eq!(triangle.mesh.vertices[2].uv, [0.0, 0.0]);
+
+    // No normals or indices in the file, so it's left unindexed with flat normals
+    let quad = &gltf.meshes[0].primitives[1];
+    assert_eq!(quad.material, Some(1));
+    assert_eq!(quad.mesh.indices, None);
+    assert_eq!(quad.mesh.vertices.len(), 6);
+    assert!(quad.mesh.vertices.iter().all(|vertex| vertex.normal == [0.0, 0.0, 1.0]));
+
+    // Triangle strips become lists that keep the winding
+    let strip = &gltf.meshes[1].primitives[0];
+    assert_eq!(strip.material, None);
+    assert_eq!(strip.mesh.indices, Some(Indices::U16(vec![0, 1, 2, 1, 3, 2])));
+}
+
+#[test]
+fn materials_carry_pbr_parameters()
+{
+    let gltf = open("embedded.gltf");
+
+    let red = &gltf.materials[0];
+    assert_eq!(red.name.as_deref(), Some("Red"));
+    assert_eq!(red.base_colour, [1.0, 0.0, 0.0, 1.0]);
+    assert_eq!((red.metallic, red.roughness), (0.0, 0.5));
+    assert!(!red.unlit && !red.double_sided);
+
+    let glow = &gltf.materials[1];
+    assert_eq!(glow.emissive, [0.0, 1.0, 0.0]);
+    assert!(glow.unlit && glow.double_sided);
+
+    let material = DefaultMaterial::from(glow);
+    let Colour(r, g, b) = material.base_colour;
+    assert_eq!([r, g, b], [0.2, 0.4, 0.6]);
+    assert!(!material.shading_enabled);
+}
+
+#[test]
+fn nodes_read_names_transforms_and_children()
+{
+    let gltf = open("embedded.gltf");
+    let scene = gltf.scene(None).unwrap();
+    assert_eq!(scene.name.as_deref(), Some("Scene"));
+    assert_eq!(scene.nodes, vec![0]);
+
+    let root = &gltf.nodes[0];
+    assert_eq!(root.children, vec![1, 2]);
+    assert_eq!(root.matrix, [[2.0, 0.0, 0.0, 0.0], [0.0, 2.0, 0.0, 0.0], [0.0, 0.0, 2.0, 0.0], [1.0, 2.0, 3.0, 1.0]]);
+
+    // Escaped in the file as é
+    assert_eq!(gltf.nodes[2].name.as_deref(), Some("Strip é"));
+    assert_eq!(gltf.nodes[2].matrix[3], [5.0, 0.0, 0.0, 1.0]);
+}
+
+#[test]
+fn scenes_spawn_as_entities_with_world_transforms()
+{
+    let mut app = App::headless();
+    let gltf = app.register_gltf(open("binary.glb"));
+    let roots = gltf.spawn_scene(&mut app.world, None).unwrap();
+    assert_eq!(roots.len(), 1);
+
+    let find = |app : &mut App, name : &str| -> Entity
+    {
+        app.world.query::<(Entity, &Name)>().iter(&app.world)
+            .find(|(_, entity_name)| entity_name.0 == name)
+            .map(|(entity, _)| entity)
+            .unwrap()
+    };
+    let (root, child, strip) = (find(&mut app, "Root"), find(&mut app, "Child"), find(&mut app, "Strip é"));
+    assert_eq!(roots[0], root);
+
+    assert_eq!(app.world.get::<Parent>(root), None);
+    assert_eq!(app.world.get::<Parent>(child), Some(&Parent(root)));
+    assert_eq!(app.world.get::<Children>(root), Some(&Children(vec![child, strip])));
+
+    // Root scales by 2 and moves to (1, 2, 3), Child then moves 1 along z and turns a quarter around y
+    let transform = *app.world.get::<Transform>(child).unwrap();
+    assert!(close(transform.position.x, 1.0) && close(transform.position.y, 2.0) && close(transform.position.z, 5.0));
+    assert!(close(transform.rotation.x, 0.0) && close(transform.rotation.y, FRAC_PI_2) && close(transform.rotation.z, 0.0));
+    assert!(close(transform.scale, 2.0));
+
+    let transform = *app.world.get::<Transform>(strip).unwrap();
+    assert!(close(transform.position.x, 11.0) && close(transform.position.y, 2.0) && close(transform.position.z, 3.0));
+
+    // Each primitive is a child entity with its own material
+    let primitives = app.world.get::<Children>(child).unwrap().0.clone();
+    assert_eq!(primitives.len(), 2);
+    for (primitive, expected) in primitives.iter().zip([Colour::RED, Colour(0.2, 0.4, 0.6)])
+    {
+        assert!(app.world.get::<Mesh>(*primitive).is_some());
+        assert_eq!(app.world.get::<Parent>(*primitive), Some(&Parent(child)));
+
+        let Colour(r, g, b) = app.world.get::<DefaultMaterial>(*primitive).unwrap().base_colour;
+        assert_eq!([r, g, b], [expected.0, expected.1, expected.2]);
+    }
+
+    assert!(gltf.spawn_scene(&mut app.world, Some(1)).is_none());
+}
+
+#[test]
+fn transforms_come_back_out_of_matrices()
+{
+    let (x, y, z) = (0.3f32, -0.7f32, 1.2f32);
+    let rotate = |angle : f32, axis : usize| -> [[f32; 3]; 3]
+    {
+        let (sin, cos) = angle.sin_cos();
+        let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+        let mut columns = [[0.0; 3]; 3];
+        columns[axis][axis] = 1.0;
+        columns[a][a] = cos;
+        columns[a][b] = sin;
+        columns[b][a] = -sin;
+        columns[b][b] = cos;
+        columns
+    };
+    let multiply = |p : [[f32; 3]; 3], q : [[f32; 3]; 3]| [0, 1, 2].map(|column| [0, 1, 2].map(|row| (0..3).map(|k| p[k][row] * q[column][k]).sum::<f32>()));
+
+    // Rx * Ry * Rz scaled by 3, then moved
+    let rotation = multiply(multiply(rotate(x, 0), rotate(y, 1)), rotate(z, 2));
+    let mut matrix = [[0.0; 4]; 4];
+    for column in 0..3
+    {
+        for row in 0..3
+        {
+            matrix[column][row] = rotation[column][row] * 3.0;
+        }
+    }
+    matrix[3] = [4.0, 5.0, 6.0, 1.0];
+
+    let transform = Transform::from_matrix(matrix);
+    assert!(close(transform.position.x, 4.0) && close(transform.position.y, 5.0) && close(transform.position.z, 6.0));
+    assert!(close(transform.rotation.x, x) && close(transform.rotation.y, y) && close(transform.rotation.z, z));
+    assert!(close(transform.scale, 3.0));
+}
+
+#[test]
+fn deep_hierarchies_spawn_without_recursing()
+{
+    const DEPTH : usize = 100_000;
+    let nodes : Vec<String> = (1..DEPTH).map(|child| format!(r#"{{ "children": [{}] }}"#, child)).chain([String::from("{}")]).collect();
+    let text = minimal(&format!(r#", "nodes": [{}], "scenes": [{{ "nodes": [0] }}]"#, nodes.join(", ")));
+
+    let mut app = App::headless();
+    let gltf = app.register_gltf(parse(&text).unwrap());
+    let roots = gltf.spawn_scene(&mut app.world, None).unwrap();
+    assert_eq!(roots.len(), 1);
+    assert_eq!(app.world.query::<&Parent>().iter(&app.world).count(), DEPTH - 1);
+}
+
+#[test]
+fn broken_files_are_errors()
+{
+    let triangle = parse(&minimal(r#", "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0 }, "indices": 1 }] }]"#)).unwrap();
+    // Without normals in the file the triangle gets flat ones, which leaves it unindexed
+    let positions : Vec<[f32; 3]> = triangle.meshes[0].primitives[0].mesh.vertices.iter().map(|vertex| vertex.position).collect();
+    assert_eq!(positions, vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]);
+
+    let broken = [
+        // Wrong version
+        minimal("").replace("\"2.0\"", "\"1.0\""),
+        // Extension we can't do without
+        minimal(r#", "extensionsRequired": ["KHR_draco_mesh_compression"]"#),
+        // Index past the last vertex
+        minimal(r#", "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0 }, "indices": 2 }] }]"#),
+        // Lines aren't triangles
+        minimal(r#", "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0 }, "mode": 1 }] }]"#),
+        // Node with two parents
+        minimal(r#", "nodes": [{ "children": [2] }, { "children": [2] }, {}]"#),
+        // Node that's its own grandparent
+        minimal(r#", "nodes": [{ "children": [1] }, { "children": [0] }]"#),
+        // Scaled unevenly
+        minimal(r#", "nodes": [{ "scale": [1, 2, 1] }]"#),
+        // Mirrored
+        minimal(r#", "nodes": [{ "scale": [-1, 1, 1] }]"#),
+        // Sheared, given as a matrix
+        minimal(r#", "nodes": [{ "matrix": [1, 0, 0, 0, 0.5, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1] }]"#),
+        // Material that doesn't exist
+        minimal(r#", "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0 }, "material": 3 }] }]"#),
+        // Buffer in a file that can't be read
+        minimal("").replace("data:application/octet-stream;base64,", "missing.bin?"),
+        // Not JSON at all
+        minimal("}"),
+        // Far more elements than the buffer view holds
+        minimal(r#", "meshes": [{ "primitives": [{ "attributes": { "POSITION": 3 } }] }]"#)
+            .replace(r#""type": "SCALAR" }
+        ]"#, r#""type": "SCALAR" },
+            { "bufferView": 0, "componentType": 5126, "count": 4294967295, "type": "VEC3" }
+        ]"#),
+        // Zero filled, but far too many to allocate
+        minimal(r#", "meshes": [{ "primitives": [{ "attributes": { "POSITION": 3 } }] }]"#)
+            .replace(r#""type": "SCALAR" }
+        ]"#, r#""type": "SCALAR" },
+            { "componentType": 5126, "count": 4294967295, "type": "VEC3" }
+        ]"#),
+        // Elements on top of each other
+        minimal(r#", "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0 } }] }]"#)
+            .replace(r#""byteLength": 36 }"#, r#""byteLength": 36, "byteStride": 0 }"#),
+        // Buffer view whose end doesn't fit in a usize
+        minimal("").replace(r#""byteOffset": 36"#, r#""byteOffset": 18446744073709551615"#),
+    ];
+
+    for text in broken
+    {
+        assert!(parse(&text).is_err(), "{}", text);
+    }
+
+    // Cut off GLB
+    let glb = std::fs::read(format!("{}/binary.glb", RES)).unwrap();
+    assert!(Gltf::from_slice(&glb[..glb.len() - 8], |_| unreachable!()).is_err());
+}
@@ -0,0 +1,236 @@
+use std::path::PathBuf;
+
+use bevy_ecs::schedule::ScheduleLabel;
+use glium::Program;
+use rad::*;
+
+#[derive(Resource, Default)]
+struct Runs(Vec<&'static str>);
+
+#[derive(Event)]
+struct Ping(u64);
+
+#[derive(Resource, Default)]
+struct Pings
+{
+    update : Vec<u64>,
+    draw : Vec<u64>,
+}
+
+#[derive(States, Default, Clone, PartialEq, Eq, Hash, Debug)]
+enum Screen
+{
+    #[default]
+    Menu,
+    Playing,
+}
+
+fn app() -> App
+{
+    let mut app = App::headless();
+    app.add_plugins(DefaultPlugins);
+    app.world.init_resource::<Runs>();
+    app
+}
+
+fn log(name : &'static str) -> impl FnMut(ResMut<Runs>)
+{
+    move |mut runs : ResMut<Runs>| runs.0.push(name)
+}
+
+#[test]
+fn step_runs_startup_once_then_update_before_draw()
+{
+    let mut app = app();
+    app.add_systems(Startup, log("startup"))
+        .add_systems(Update, log("update"))
+        .add_systems(Draw, log("draw"))
+        .add_systems(End, log("end"));
+
+    app.step(2);
+    app.step(1);
+    assert_eq!(app.world.resource::<Runs>().0, ["startup", "update", "draw", "update", "draw", "update", "draw"]);
+
+    // One fixed step per frame, however long the frames really took
+    assert_eq!(app.world.resource::<FixedTime>().tick_count(), 3);
+    assert_eq!(app.world.resource::<Time>().frame_count(), 3);
+    assert!(!app.finished_running());
+}
+
+#[test]
+fn ordered_schedules_run_around_their_anchor()
+{
+    #[derive(ScheduleLabel, Hash, Debug, PartialEq, Eq, Clone, Copy)]
+    struct Before;
+    #[derive(ScheduleLabel, Hash, Debug, PartialEq, Eq, Clone, Copy)]
+    struct After;
+
+    let mut app = app();
+    app.add_schedule_before(Before, Update)
+        .add_schedule_after(After, Update)
+        .add_systems(Before, log("before"))
+        .add_systems(Update, log("update"))
+        .add_systems(After, log("after"));
+
+    app.step(1);
+    assert_eq!(app.world.resource::<Runs>().0, ["before", "update", "after"]);
+}
+
+#[test]
+#[should_panic(expected = "already been ordered")]
+fn schedules_can_only_be_added_once()
+{
+    #[derive(ScheduleLabel, Hash, Debug, PartialEq, Eq, Clone, Copy)]
+    struct Twice;
+
+    let mut app = app();
+    app.add_schedule(Phase::Frame, Twice)
+        .add_schedule(Phase::Fixed, Twice);
+}
+
+#[test]
+fn exit_requests_run_end_unless_vetoed()
+{
+    let mut app = app();
+    app.add_systems(End, log("end"))
+        .add_systems(Update, |mut exit : ResMut<AppExit>, time : Res<FixedTime>|
+        {
+            if time.tick_count() >= 2
+            {
+                exit.request();
+            }
+            if time.tick_count() == 2
+            {
+                exit.veto();
+            }
+        });
+
+    app.step(10);
+    assert!(app.finished_running());
+    assert_eq!(app.world.resource::<Runs>().0, ["end"]);
+    assert_eq!(app.world.resource::<FixedTime>().tick_count(), 3);
+}
+
+#[test]
+fn events_reach_update_and_draw_readers()
+{
+    let mut app = app();
+    app.add_event::<Ping>();
+    app.world.init_resource::<Pings>();
+    app.add_systems(Update, (
+            |mut pings : EventWriter<Ping>, time : Res<FixedTime>| { pings.send(Ping(time.tick_count())); },
+            |mut reader : EventReader<Ping>, mut pings : ResMut<Pings>| pings.update.extend(reader.read().map(|ping| ping.0)),
+        ).chain())
+        .add_systems(Draw, |mut reader : EventReader<Ping>, mut pings : ResMut<Pings>| pings.draw.extend(reader.read().map(|ping| ping.0)));
+
+    app.step(4);
+    let pings = app.world.resource::<Pings>();
+    assert_eq!(pings.update, [1, 2, 3, 4]);
+    assert_eq!(pings.draw, [1, 2, 3, 4]);
+
+    // Older events are dropped as the buffers are swapped
+    assert_eq!(app.world.resource::<Events<Ping>>().len(), 1);
+}
+
+#[test]
+fn state_transitions_run_enter_and_exit_schedules()
+{
+    let mut app = app();
+    app.init_state::<Screen>()
+        .add_systems(OnEnter(Screen::Menu), log("enter menu"))
+        .add_systems(OnExit(Screen::Menu), log("exit menu"))
+        .add_systems(OnEnter(Screen::Playing), log("enter playing"))
+        .add_systems(Update, (|mut next : ResMut<NextState<Screen>>| next.set(Screen::Playing)).run_if(in_state(Screen::Menu)))
+        .add_systems(Update, log("playing").run_if(in_state(Screen::Playing)));
+
+    app.step(3);
+    assert_eq!(app.world.resource::<Runs>().0, ["enter menu", "exit menu", "enter playing", "playing", "playing"]);
+    assert_eq!(*app.world.resource::<State<Screen>>().get(), Screen::Playing);
+}
+
+#[test]
+fn headless_meshes_and_shaders_use_the_same_handles_as_opengl()
+{
+    #[derive(Resource, Default)]
+    struct Seen
+    {
+        meshes : Vec<AssetEvent<OGLMesh>>,
+        programs : Vec<AssetEvent<Program>>,
+    }
+
+    let mut app = app();
+    app.world.init_resource::<Seen>();
+    app.add_systems(Update, |mut meshes : EventReader<AssetEvent<OGLMesh>>, mut programs : EventReader<AssetEvent<Program>>, mut seen : ResMut<Seen>|
+    {
+        seen.meshes.extend(meshes.read().copied());
+        seen.programs.extend(programs.read().copied());
+    });
+
+    let mesh = app.register_mesh(MeshBuilder::cube(1.0, 1));
+    let other = app.register_mesh(MeshBuilder::cube(1.0, 1));
+    let shader = app.create_program(&ShaderSource::new("void main() {}", "void main() {}"));
+    assert_ne!(mesh.handle, other.handle);
+
+    let mut broken = MeshBuilder::cube(1.0, 1);
+    broken.indices = Some(Indices::U16(vec![0, 1, 60000]));
+    assert!(app.try_register_mesh(broken).is_err());
+
+    let (weak_mesh, weak_shader) = (mesh.handle.downgrade(), shader.handle.downgrade());
+    app.step(1);
+    drop((mesh, shader));
+    // Freed at the end of this frame, read by Update in the next
+    app.step(2);
+
+    let seen = app.world.resource::<Seen>();
+    assert_eq!(seen.meshes, [
+        AssetEvent::Added { handle: weak_mesh },
+        AssetEvent::Added { handle: other.handle.downgrade() },
+        AssetEvent::Removed { handle: weak_mesh },
+    ]);
+    assert_eq!(seen.programs, [AssetEvent::Added { handle: weak_shader }, AssetEvent::Removed { handle: weak_shader }]);
+}
+
+/// A fresh folder in the temp dir, removed when dropped even if the test fails
+struct TempDir(PathBuf);
+
+impl TempDir
+{
+    fn new(name : &str) -> Self
+    {
+        let path = std::env::temp_dir().join(format!("rad-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+}
+
+impl Drop for TempDir
+{
+    fn drop(&mut self)
+    {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn the_asset_server_loads_into_headless_apps()
+{
+    let root = TempDir::new("headless");
+    std::fs::write(root.0.join("triangle.obj"), "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n").unwrap();
+
+    let mut app = App::headless();
+    app.add_plugins(DefaultPlugins.build().set(AssetPlugin { root: root.0.clone(), ..Default::default() }));
+    let mesh = app.world.resource::<AssetServer>().load::<OGLMesh>("triangle.obj");
+
+    for _ in 0..500
+    {
+        if !matches!(app.world.resource::<AssetServer>().load_state(&mesh), LoadState::Loading)
+        {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        app.step(1);
+    }
+
+    assert!(matches!(app.world.resource::<AssetServer>().load_state(&mesh), LoadState::Loaded));
+}
@@ -0,0 +1,56 @@
+use rad::{App, Indices, MeshBuilder, RadError, Vertex};
+
+fn vertices(count : usize) -> Vec<Vertex>
+{
+    vec![Vertex { position: [0.0; 3], normal: [0.0, 0.0, 1.0], uv: [0.0; 2] }; count]
+}
+
+#[test]
+fn narrowest_width_is_chosen()
+{
+    assert_eq!(Indices::new(vec![0, 1, 65535]), Indices::U16(vec![0, 1, 65535]));
+    assert_eq!(Indices::new(vec![0, 1, 65536]), Indices::U32(vec![0, 1, 65536]));
+    assert_eq!(Indices::from(vec![2u32, 1, 0]), Indices::U16(vec![2, 1, 0]));
+}
+
+#[test]
+fn sequential_indices_past_u16_are_u32()
+{
+    let indices = Indices::sequential(70_000).unwrap();
+
+    assert!(matches!(indices, Indices::U32(_)));
+    assert_eq!(indices.len(), 70_000);
+    assert_eq!(indices.iter().last(), Some(69_999));
+}
+
+#[test]
+fn large_meshes_keep_every_index()
+{
+    let count = 100_000;
+    let mesh = MeshBuilder::new(vertices(count), Some(Indices::new((0..count as u32).rev().collect())));
+
+    assert!(mesh.validate().is_ok());
+    let bytes = mesh.to_bytes().unwrap();
+    let read = MeshBuilder::from_bytes(&bytes).unwrap();
+    assert_eq!(read.indices, mesh.indices);
+}
+
+#[test]
+fn out_of_range_index_is_an_error()
+{
+    let mut app = App::headless();
+    let mesh = MeshBuilder::new(vertices(3), Some(Indices::U16(vec![0, 1, 3])));
+
+    assert!(matches!(app.try_register_mesh(mesh), Err(RadError::MeshUpload(_))));
+}
+
+#[test]
+fn huge_counts_in_mesh_data_are_an_error()
+{
+    assert!(matches!(MeshBuilder::from_bytes(&u32::MAX.to_le_bytes()), Err(RadError::Parse(_))));
+
+    let mut bytes = 0u32.to_le_bytes().to_vec();
+    bytes.push(2);
+    bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+    assert!(matches!(MeshBuilder::from_bytes(&bytes), Err(RadError::Parse(_))));
+}
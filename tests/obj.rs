@@ -0,0 +1,133 @@
+use std::{error::Error, path::PathBuf, thread::sleep, time::Duration};
+
+use rad::*;
+
+const MONKEY : &str = include_str!("../examples/res/monkey.obj");
+
+#[test]
+fn monkey_is_welded()
+{
+    let faces : Vec<&str> = MONKEY.lines().filter_map(|line| line.strip_prefix("f ")).collect();
+    let corners = faces.len() * 3;
+    // Every distinct v/vt/vn reference is at most one welded vertex
+    let references : std::collections::HashSet<&str> = faces.iter().flat_map(|face| face.split_whitespace()).collect();
+
+    let mesh = MeshBuilder::from_obj(MONKEY);
+    let indices = mesh.indices.as_ref().expect("OBJ meshes should be indexed");
+
+    assert_eq!(indices.len(), corners);
+    assert!(mesh.vertices.len() < corners, "{} vertices for {} corners", mesh.vertices.len(), corners);
+    assert!(mesh.vertices.len() <= references.len(), "{} vertices for {} distinct corners", mesh.vertices.len(), references.len());
+    assert!(indices.iter().all(|index| (index as usize) < mesh.vertices.len()));
+}
+
+#[test]
+fn welded_vertices_are_unique()
+{
+    let mesh = MeshBuilder::from_obj(MONKEY);
+    let mut seen = std::collections::HashSet::new();
+
+    for vertex in &mesh.vertices
+    {
+        let key : Vec<u32> = vertex.position.iter().chain(&vertex.normal).chain(&vertex.uv).map(|value| value.to_bits()).collect();
+        assert!(seen.insert(key), "vertex {:?} appears twice", vertex.position);
+    }
+}
+
+#[test]
+fn shared_corners_weld()
+{
+    // Two triangles sharing an edge, with matching uvs and normals along it
+    let quad = "\
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 1
+f 1/1/1 2/2/1 3/3/1
+f 1/1/1 3/3/1 4/4/1
+";
+    let mesh = MeshBuilder::from_obj(quad);
+
+    assert_eq!(mesh.vertices.len(), 4);
+    assert_eq!(mesh.indices, Some(Indices::U16(vec![0, 1, 2, 0, 2, 3])));
+}
+
+/// Keeps what the OBJ loader produced instead of uploading it
+struct LoadedObj(MeshBuilder);
+
+impl CpuAsset for LoadedObj {}
+
+struct KeepObj;
+
+impl AssetLoader for KeepObj
+{
+    type Asset = LoadedObj;
+
+    fn extensions(&self) -> &[&str]
+    {
+        &["obj"]
+    }
+
+    fn load(&self, bytes : Vec<u8>, context : &mut LoadContext) -> Result<LoadedObj, Box<dyn Error + Send + Sync>>
+    {
+        ObjLoader.load(bytes, context).map(LoadedObj)
+    }
+}
+
+/// A fresh folder in the temp dir, removed when dropped even if the test fails
+struct TempDir(PathBuf);
+
+impl TempDir
+{
+    fn new(name : &str) -> Self
+    {
+        let path = std::env::temp_dir().join(format!("rad-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+}
+
+impl Drop for TempDir
+{
+    fn drop(&mut self)
+    {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn the_loader_computes_missing_normals()
+{
+    let dir = TempDir::new("obj-normals");
+    std::fs::write(dir.0.join("quad.obj"), "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf 1 2 3\nf 1 3 4\n").unwrap();
+
+    let mut app = App::headless();
+    app.add_plugins(DefaultPlugins.build().set(AssetPlugin { root: dir.0.clone(), ..Default::default() }));
+    app.init_asset::<LoadedObj>();
+    app.add_asset_loader(KeepObj);
+
+    let handle : Handle<LoadedObj> = app.world.resource::<AssetServer>().load("quad.obj");
+    for _ in 0..500
+    {
+        app.step(1);
+        if app.world.resource::<AssetServer>().load_state(&handle) != LoadState::Loading
+        {
+            break;
+        }
+        sleep(Duration::from_millis(5));
+    }
+
+    let assets = app.world.resource::<Assets<LoadedObj>>();
+    let mesh = &assets.get_asset(&handle).expect("the quad should load").0;
+    assert_eq!(mesh.vertices.len(), 4);
+    for vertex in &mesh.vertices
+    {
+        assert_eq!(vertex.normal, [0.0, 0.0, 1.0]);
+    }
+}
@@ -0,0 +1,106 @@
+use rad::*;
+
+/// What each plugin's build recorded, in order
+#[derive(Resource, Default)]
+struct Built(Vec<String>);
+
+fn record(app : &mut App, entry : String)
+{
+    app.world.get_resource_or_insert_with(Built::default).0.push(entry);
+}
+
+fn built(app : &App) -> Vec<String>
+{
+    app.world.get_resource::<Built>().map(|built| built.0.clone()).unwrap_or_default()
+}
+
+struct First(u32);
+
+impl Plugin for First
+{
+    fn build(&self, app : &mut App)
+    {
+        record(app, format!("first {}", self.0));
+    }
+}
+
+struct Second(u32);
+
+impl Plugin for Second
+{
+    fn build(&self, app : &mut App)
+    {
+        record(app, format!("second {}", self.0));
+    }
+}
+
+struct Repeatable;
+
+impl Plugin for Repeatable
+{
+    fn build(&self, app : &mut App)
+    {
+        record(app, "repeatable".to_string());
+    }
+
+    fn is_unique(&self) -> bool
+    {
+        false
+    }
+}
+
+struct Pair;
+
+impl PluginGroup for Pair
+{
+    fn build(self) -> PluginGroupBuilder
+    {
+        PluginGroupBuilder::new().add(First(1)).add(Second(1))
+    }
+}
+
+#[test]
+fn set_replaces_a_plugin_where_it_is()
+{
+    let mut app = App::headless();
+    app.add_plugins(Pair.build().set(First(2)));
+    assert_eq!(built(&app), ["first 2", "second 1"]);
+}
+
+#[test]
+#[should_panic(expected = "isn't part of this plugin group")]
+fn set_only_replaces_plugins_in_the_group()
+{
+    let _ = PluginGroupBuilder::new().add(First(1)).set(Second(2));
+}
+
+#[test]
+fn disabled_plugins_are_not_built()
+{
+    let mut app = App::headless();
+    app.add_plugins(Pair.build().disable::<First>());
+    assert_eq!(built(&app), ["second 1"]);
+    assert!(!app.is_plugin_added::<First>());
+    assert!(app.is_plugin_added::<Second>());
+
+    let mut app = App::headless();
+    app.add_plugins(Pair.build().disable::<First>().enable::<First>());
+    assert_eq!(built(&app), ["first 1", "second 1"]);
+}
+
+#[test]
+#[should_panic(expected = "has already been added")]
+fn unique_plugins_can_only_be_added_once()
+{
+    let mut app = App::headless();
+    app.add_plugins(First(1));
+    app.add_plugins(Pair);
+}
+
+#[test]
+fn plugins_that_are_not_unique_can_be_added_again()
+{
+    let mut app = App::headless();
+    app.add_plugins((Repeatable, Repeatable));
+    assert_eq!(built(&app), ["repeatable", "repeatable"]);
+}
@@ -0,0 +1,127 @@
+use rad::{MeshBuilder, Vertex};
+
+fn sub(a : [f32; 3], b : [f32; 3]) -> [f32; 3]
+{
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a : [f32; 3], b : [f32; 3]) -> [f32; 3]
+{
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn dot(a : [f32; 3], b : [f32; 3]) -> f32
+{
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Checks counts, that normals are unit length, uvs are in range and every triangle winds to face along its normals
+fn check(name : &str, mesh : &MeshBuilder, vertex_count : usize, index_count : usize)
+{
+    check_with_max_u(name, mesh, vertex_count, index_count, 1.0);
+}
+
+/// Same as `check`, for meshes whose u runs past 1 where the texture wraps around
+fn check_with_max_u(name : &str, mesh : &MeshBuilder, vertex_count : usize, index_count : usize, max_u : f32)
+{
+    let indices : Vec<u32> = mesh.indices.as_ref().expect("primitives are indexed").iter().collect();
+
+    assert_eq!(mesh.vertices.len(), vertex_count, "{} vertex count", name);
+    assert_eq!(indices.len(), index_count, "{} index count", name);
+    assert!(mesh.validate().is_ok(), "{} has indices out of range", name);
+
+    for Vertex { normal, uv, .. } in &mesh.vertices
+    {
+        assert!((dot(*normal, *normal).sqrt() - 1.0).abs() < 1e-4, "{} has a normal of length {}", name, dot(*normal, *normal).sqrt());
+        assert!((0.0..=max_u).contains(&uv[0]) && (0.0..=1.0).contains(&uv[1]), "{} has uv {:?}", name, uv);
+    }
+
+    for triangle in indices.chunks(3)
+    {
+        let [a, b, c] = [0, 1, 2].map(|corner| mesh.vertices[triangle[corner] as usize]);
+        let face = cross(sub(b.position, a.position), sub(c.position, a.position));
+        assert!(dot(face, face) > 0.0, "{} has a degenerate triangle {:?}", name, triangle);
+
+        let normal = [0, 1, 2].map(|axis| a.normal[axis] + b.normal[axis] + c.normal[axis]);
+        assert!(dot(face, normal) > 0.0, "{} triangle {:?} faces away from its normals", name, triangle);
+    }
+}
+
+#[test]
+fn plane()
+{
+    check("plane", &MeshBuilder::plane(2.0, 1), 4, 6);
+    check("subdivided plane", &MeshBuilder::plane(2.0, 4), 25, 96);
+}
+
+#[test]
+fn cube()
+{
+    let cube = MeshBuilder::cube(2.0, 1);
+    check("cube", &cube, 24, 36);
+    assert!(cube.vertices.iter().all(|vertex| vertex.position.iter().all(|p| p.abs() == 1.0)));
+
+    check("subdivided cube", &MeshBuilder::cube(1.0, 3), 6 * 16, 6 * 9 * 6);
+}
+
+#[test]
+fn uv_sphere()
+{
+    let sphere = MeshBuilder::uv_sphere(2.0, 16, 8);
+    check("uv sphere", &sphere, 17 * 9, 6 * 16 * 7);
+    assert!(sphere.vertices.iter().all(|vertex| (dot(vertex.position, vertex.position).sqrt() - 2.0).abs() < 1e-4));
+}
+
+#[test]
+fn ico_sphere()
+{
+    // The icosahedron has 3 corners left behind by the seam, the subdivided sphere 13, and 8 more for the 5 triangles at each pole
+    check_with_max_u("icosahedron", &MeshBuilder::ico_sphere(1.0, 0), 12 + 3, 60, 1.1);
+    let sphere = MeshBuilder::ico_sphere(0.5, 2);
+    check_with_max_u("ico sphere", &sphere, 10 * 16 + 2 + 13 + 8, 60 * 16, 1.1);
+    assert!(sphere.vertices.iter().all(|vertex| (dot(vertex.position, vertex.position).sqrt() - 0.5).abs() < 1e-4));
+
+    // No triangle stretches back across the texture
+    let indices : Vec<u32> = sphere.indices.as_ref().unwrap().iter().collect();
+    for triangle in indices.chunks(3)
+    {
+        let u = [0, 1, 2].map(|corner| sphere.vertices[triangle[corner] as usize].uv[0]);
+        let spread = u.iter().fold(0.0f32, |a, b| a.max(*b)) - u.iter().fold(f32::MAX, |a, b| a.min(*b));
+        assert!(spread <= 0.5, "triangle {:?} spans u {:?}", triangle, u);
+    }
+}
+
+#[test]
+fn cylinder()
+{
+    check("cylinder", &MeshBuilder::cylinder(1.0, 2.0, 12, 3), 13 * 4 + 2 * 13, 6 * 12 * 3 + 6 * 12);
+}
+
+#[test]
+fn cone()
+{
+    check("cone", &MeshBuilder::cone(1.0, 2.0, 12), 12 + 13 + 13, 3 * 12 + 3 * 12);
+}
+
+#[test]
+fn capsule()
+{
+    let capsule = MeshBuilder::capsule(0.5, 1.0, 12, 4);
+    check("capsule", &capsule, 2 * 5 * 13, 12 * 12 * 4);
+
+    let top = capsule.vertices.iter().map(|vertex| vertex.position[1]).fold(f32::MIN, f32::max);
+    assert!((top - 1.0).abs() < 1e-4);
+}
+
+#[test]
+fn torus()
+{
+    check("torus", &MeshBuilder::torus(1.0, 0.25, 24, 8), 25 * 9, 6 * 24 * 8);
+}
+
+#[test]
+fn subdivisions_are_clamped()
+{
+    check("flattest sphere", &MeshBuilder::uv_sphere(1.0, 0, 0), 4 * 3, 6 * 3);
+    check("flattest torus", &MeshBuilder::torus(1.0, 0.25, 0, 0), 16, 6 * 9);
+}
@@ -0,0 +1,189 @@
+use rad::{Aabb, Indices, MeshBuilder, Vertex};
+
+fn close(a : [f32; 3], b : [f32; 3]) -> bool
+{
+    (0..3).all(|axis| (a[axis] - b[axis]).abs() < 1e-4)
+}
+
+fn length(a : [f32; 3]) -> f32
+{
+    (a[0] * a[0] + a[1] * a[1] + a[2] * a[2]).sqrt()
+}
+
+/// Whether every triangle winds counter-clockwise seen from the side its normals point to
+fn faces_along_normals(mesh : &MeshBuilder) -> bool
+{
+    let corners : Vec<u32> = match &mesh.indices
+    {
+        Some(indices) => indices.iter().collect(),
+        None => (0..mesh.vertices.len() as u32).collect(),
+    };
+
+    corners.chunks(3).all(|triangle|
+    {
+        let [a, b, c] = [0, 1, 2].map(|corner| mesh.vertices[triangle[corner] as usize]);
+        let (e1, e2) = ([0, 1, 2].map(|i| b.position[i] - a.position[i]), [0, 1, 2].map(|i| c.position[i] - a.position[i]));
+        let face = [e1[1] * e2[2] - e1[2] * e2[1], e1[2] * e2[0] - e1[0] * e2[2], e1[0] * e2[1] - e1[1] * e2[0]];
+        (0..3).map(|i| face[i] * (a.normal[i] + b.normal[i] + c.normal[i])).sum::<f32>() > 0.0
+    })
+}
+
+fn without_normals(mut mesh : MeshBuilder) -> MeshBuilder
+{
+    mesh.vertices.iter_mut().for_each(|vertex| vertex.normal = [0.0; 3]);
+    mesh
+}
+
+#[test]
+fn flat_normals()
+{
+    let mut cube = without_normals(MeshBuilder::cube(2.0, 1));
+    cube.compute_flat_normals().unwrap();
+
+    assert_eq!(cube.indices, None);
+    assert_eq!(cube.vertices.len(), 36);
+    assert!(cube.vertices.iter().all(|vertex| (length(vertex.normal) - 1.0).abs() < 1e-4));
+    assert!(faces_along_normals(&cube));
+    assert_eq!(cube.vertices.iter().filter(|vertex| close(vertex.normal, [0.0, 1.0, 0.0])).count(), 6);
+}
+
+#[test]
+fn smooth_normals()
+{
+    let mut sphere = without_normals(MeshBuilder::uv_sphere(1.0, 24, 12));
+    sphere.compute_smooth_normals().unwrap();
+
+    // On a sphere the normal points the same way as the position, including along the uv seam
+    for vertex in &sphere.vertices
+    {
+        let dot : f32 = (0..3).map(|i| vertex.normal[i] * vertex.position[i]).sum();
+        assert!(dot > 0.99, "normal {:?} at {:?}", vertex.normal, vertex.position);
+    }
+}
+
+#[test]
+fn tangents_follow_u()
+{
+    let mut plane = MeshBuilder::plane(1.0, 2);
+    plane.generate_tangents().unwrap();
+
+    let tangents = plane.tangents.as_ref().unwrap();
+    assert_eq!(tangents.len(), plane.vertices.len());
+    assert!(tangents.iter().all(|tangent| *tangent == [1.0, 0.0, 0.0, 1.0]), "{:?}", tangents);
+    assert!(plane.validate().is_ok());
+
+    let read = MeshBuilder::from_bytes(&plane.to_bytes().unwrap()).unwrap();
+    assert_eq!(read.tangents, plane.tangents);
+}
+
+#[test]
+fn tangents_are_perpendicular_to_normals()
+{
+    let mut torus = MeshBuilder::torus(1.0, 0.3, 16, 8);
+    torus.generate_tangents().unwrap();
+
+    for (vertex, tangent) in torus.vertices.iter().zip(torus.tangents.as_ref().unwrap())
+    {
+        let xyz = [tangent[0], tangent[1], tangent[2]];
+        assert!((length(xyz) - 1.0).abs() < 1e-4);
+        assert!((0..3).map(|i| xyz[i] * vertex.normal[i]).sum::<f32>().abs() < 1e-4);
+        assert!(tangent[3] == 1.0 || tangent[3] == -1.0);
+    }
+}
+
+#[test]
+fn processing_rejects_out_of_range_indices()
+{
+    let mut broken = MeshBuilder::plane(1.0, 1);
+    broken.indices = Some(Indices::new(vec![0, 1, 9]));
+    let before = broken.indices.clone();
+
+    assert!(broken.compute_flat_normals().is_err());
+    assert!(broken.compute_smooth_normals().is_err());
+    assert!(broken.generate_tangents().is_err());
+    assert_eq!(broken.indices, before);
+    assert_eq!(broken.tangents, None);
+}
+
+#[test]
+fn bounds()
+{
+    let cube = MeshBuilder::cube(2.0, 1);
+    assert_eq!(cube.aabb(), Some(Aabb { min: [-1.0; 3], max: [1.0; 3] }));
+
+    let sphere = MeshBuilder::ico_sphere(3.0, 1).bounding_sphere().unwrap();
+    assert!(close(sphere.centre, [0.0; 3]));
+    assert!((sphere.radius - 3.0).abs() < 1e-4);
+
+    assert_eq!(MeshBuilder::new(vec![], None).aabb(), None);
+}
+
+#[test]
+fn transform()
+{
+    let mut cube = MeshBuilder::cube(2.0, 1);
+    // Scale x by 2 then move up by 5, column major
+    cube.transform([[2.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0], [0.0, 5.0, 0.0, 1.0]]);
+
+    assert_eq!(cube.aabb(), Some(Aabb { min: [-2.0, 4.0, -1.0], max: [2.0, 6.0, 1.0] }));
+    assert!(cube.vertices.iter().all(|vertex| (length(vertex.normal) - 1.0).abs() < 1e-4));
+    assert!(faces_along_normals(&cube));
+}
+
+#[test]
+fn mirroring_keeps_faces_outward()
+{
+    let mut sphere = MeshBuilder::uv_sphere(1.0, 12, 6);
+    sphere.generate_tangents().unwrap();
+    sphere.transform([[-1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0]]);
+
+    assert!(faces_along_normals(&sphere));
+    for vertex in &sphere.vertices
+    {
+        let dot : f32 = (0..3).map(|i| vertex.normal[i] * vertex.position[i]).sum();
+        assert!(dot > 0.99);
+    }
+}
+
+#[test]
+fn merge()
+{
+    let mut mesh = MeshBuilder::cube(1.0, 1);
+    let plane = MeshBuilder::plane(1.0, 1);
+    mesh.merge(&plane);
+
+    assert_eq!(mesh.vertices.len(), 24 + 4);
+    let indices : Vec<u32> = mesh.indices.as_ref().unwrap().iter().collect();
+    assert_eq!(indices.len(), 36 + 6);
+    assert_eq!(&indices[36..], &[24, 26, 25, 25, 26, 27]);
+    assert!(mesh.validate().is_ok());
+}
+
+#[test]
+fn merge_unindexed()
+{
+    let triangle = |x : f32| MeshBuilder::new(
+        [[x, 0.0, 0.0], [x + 1.0, 0.0, 0.0], [x, 1.0, 0.0]].map(|position| Vertex { position, normal: [0.0, 0.0, 1.0], uv: [0.0; 2] }).to_vec(),
+        None,
+    );
+
+    let mut mesh = triangle(0.0);
+    mesh.merge(&triangle(2.0));
+    assert_eq!(mesh.vertices.len(), 6);
+    assert_eq!(mesh.indices, None);
+
+    let mut indexed = MeshBuilder::plane(1.0, 1);
+    indexed.merge(&triangle(2.0));
+    assert_eq!(indexed.indices.as_ref().unwrap().iter().skip(6).collect::<Vec<_>>(), vec![4, 5, 6]);
+}
+
+#[test]
+fn flip_winding()
+{
+    let mut plane = MeshBuilder::plane(1.0, 1);
+    let before = plane.indices.clone().unwrap();
+    plane.flip_winding();
+
+    assert!(!faces_along_normals(&plane));
+    assert_eq!(plane.indices, Some(Indices::new(before.iter().collect::<Vec<_>>().chunks(3).flat_map(|t| [t[0], t[2], t[1]]).collect())));
+}
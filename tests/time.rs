@@ -0,0 +1,177 @@
+use std::time::Duration;
+
+use rad::*;
+
+fn ms(millis : u64) -> Duration
+{
+    Duration::from_millis(millis)
+}
+
+#[test]
+fn once_timers_finish_and_stay_finished()
+{
+    let mut timer = Timer::new(ms(100), TimerMode::Once);
+
+    assert!(!timer.tick(ms(60)).finished());
+    assert!(timer.tick(ms(60)).just_finished());
+    assert_eq!(timer.elapsed(), ms(100));
+    assert_eq!(timer.remaining(), Duration::ZERO);
+
+    assert!(timer.tick(ms(60)).finished());
+    assert!(!timer.just_finished());
+
+    timer.reset();
+    assert!(!timer.finished());
+    assert_eq!(timer.fraction(), 0.0);
+}
+
+#[test]
+fn repeating_timers_wrap_and_count_every_finish()
+{
+    let mut timer = Timer::new(ms(100), TimerMode::Repeating);
+
+    timer.tick(ms(250));
+    assert!(timer.just_finished());
+    assert_eq!(timer.times_finished_this_tick(), 2);
+    assert_eq!(timer.elapsed(), ms(50));
+
+    timer.tick(ms(10));
+    assert!(!timer.finished());
+    assert_eq!(timer.times_finished_this_tick(), 0);
+}
+
+#[test]
+fn paused_timers_and_stopwatches_hold_still()
+{
+    let mut timer = Timer::new(ms(100), TimerMode::Once);
+    timer.pause();
+    assert!(!timer.tick(ms(200)).finished());
+    timer.unpause();
+    assert!(timer.tick(ms(200)).finished());
+
+    let mut stopwatch = Stopwatch::new();
+    stopwatch.tick(ms(30));
+    stopwatch.pause();
+    stopwatch.tick(ms(30));
+    assert_eq!(stopwatch.elapsed(), ms(30));
+}
+
+#[test]
+fn the_app_ticks_clocks_timers_and_run_conditions()
+{
+    #[derive(Resource, Default)]
+    struct Counts
+    {
+        updates : u32,
+        timed : u32,
+    }
+
+    let mut app = App::headless();
+    app.add_plugins(DefaultPlugins);
+    app.world.init_resource::<Counts>();
+    app.add_systems(Update, (
+        |mut counts : ResMut<Counts>| counts.updates += 1,
+        (|mut counts : ResMut<Counts>| counts.timed += 1).run_if(on_timer(ms(100))),
+    ));
+    let timer = app.spawn(Timer::from_seconds(0.5, TimerMode::Once)).id();
+
+    app.step(60);
+
+    let counts = app.world.resource::<Counts>();
+    assert_eq!((counts.updates, counts.timed), (60, 10));
+    assert!(app.world.get::<Timer>(timer).unwrap().finished());
+
+    let time = app.world.resource::<Time>();
+    assert_eq!(time.frame_count(), 60);
+    assert!((time.elapsed_seconds() - 1.0).abs() < 1e-3);
+    assert!((time.smoothed_delta_seconds() - 1.0 / 60.0).abs() < 1e-6);
+}
+
+#[test]
+fn time_scale_slows_scaled_clocks_only()
+{
+    let mut app = App::headless();
+    app.add_plugins(DefaultPlugins);
+    app.world.resource_mut::<Time>().set_time_scale(0.5);
+    app.step(10);
+
+    let time = app.world.resource::<Time>();
+    assert!((time.elapsed().as_secs_f64() - time.raw_elapsed().as_secs_f64() * 0.5).abs() < 1e-6);
+
+    // Update still runs every step, it's FixedTime's delta that shrinks
+    let fixed = app.world.resource::<FixedTime>();
+    assert_eq!(fixed.tick_count(), 10);
+    assert!((fixed.delta_seconds_f64() - 0.5 / 60.0).abs() < 1e-6);
+
+    app.world.resource_mut::<Time>().pause();
+    app.step(1);
+    assert_eq!(app.world.resource::<FixedTime>().delta(), Duration::ZERO);
+}
+
+#[test]
+fn time_scale_stays_finite()
+{
+    let mut time = Time::default();
+    time.set_time_scale(f64::INFINITY);
+    assert_eq!(time.time_scale(), MAX_TIME_SCALE);
+
+    time.set_time_scale(-2.0);
+    assert_eq!(time.time_scale(), 0.0);
+
+    time.set_time_scale(f64::NAN);
+    assert_eq!(time.time_scale(), 0.0);
+
+    // A very long frame at the largest scale still fits in a Duration
+    time.set_time_scale(1e300);
+    assert_eq!(time.scale(Duration::from_secs(60 * 60 * 24)), Duration::from_secs(60 * 60 * 24 * 1000));
+}
+
+#[test]
+#[should_panic(expected = "positive, finite rate")]
+fn fixed_time_needs_a_positive_rate()
+{
+    FixedTime::from_hz(0.0);
+}
+
+#[test]
+#[should_panic(expected = "step above zero")]
+fn fixed_time_needs_a_step_above_zero()
+{
+    FixedTime::new(Duration::ZERO);
+}
+
+#[test]
+#[should_panic(expected = "step above zero")]
+fn fixed_time_step_cant_be_set_to_zero()
+{
+    let mut fixed = FixedTime::default();
+    fixed.set_step(ms(20));
+    assert_eq!(fixed.step(), ms(20));
+    fixed.set_step(Duration::ZERO);
+}
+
+#[test]
+#[allow(deprecated)]
+fn delta_time_follows_the_schedule_that_is_running()
+{
+    #[derive(Resource, Default)]
+    struct Seen
+    {
+        update : f64,
+        draw : f64,
+    }
+
+    let mut app = App::headless();
+    app.add_plugins(DefaultPlugins);
+    app.world.init_resource::<Seen>();
+    app.world.resource_mut::<Time>().set_time_scale(2.0);
+    app.add_systems(Update, |delta : Res<DeltaTime>, mut seen : ResMut<Seen>| seen.update = delta.delta_time)
+        .add_systems(Draw, |delta : Res<DeltaTime>, mut seen : ResMut<Seen>| seen.draw = delta.delta_time);
+
+    app.step(1);
+
+    let seen = app.world.resource::<Seen>();
+    assert!((seen.update - app.world.resource::<FixedTime>().delta_seconds_f64()).abs() < 1e-9);
+    assert!((seen.draw - app.world.resource::<Time>().delta_seconds_f64()).abs() < 1e-9);
+    assert!((seen.draw - 2.0 / 60.0).abs() < 1e-6);
+}